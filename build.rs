@@ -1,3 +1,6 @@
+use std::fs;
+use std::process::Command;
+
 fn main() {
     cxx_build::bridge("src/event2/quest1.rs")
         .file("src/event2/quest1.cc")
@@ -5,4 +8,22 @@ fn main() {
         .compile("ec");
     println!("cargo:rerun-if-changed=src/event2/quest1.cc");
     println!("cargo:rerun-if-changed=include/event2/quest1.h");
+
+    // Record the git revision so that submissions can be traced back to the code.
+    if let Ok(output) = Command::new("git")
+        .args(["describe", "--always", "--dirty"])
+        .output()
+        && output.status.success()
+    {
+        let revision = String::from_utf8_lossy(&output.stdout);
+        println!("cargo:rustc-env=EC_GIT_REVISION={}", revision.trim());
+    }
+    println!("cargo:rerun-if-changed=.git/HEAD");
+    // Committing on a branch only moves the branch ref, not HEAD itself.
+    if let Ok(head) = fs::read_to_string(".git/HEAD")
+        && let Some(branch) = head.trim().strip_prefix("ref: ")
+    {
+        println!("cargo:rerun-if-changed=.git/{branch}");
+    }
+    println!("cargo:rerun-if-changed=.git/index");
 }
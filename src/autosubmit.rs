@@ -1,46 +1,91 @@
-use std::collections::HashMap;
-use std::fs;
-
-use serde::Deserialize;
-use serde::Serialize;
+use std::fmt;
+use std::time::Duration;
+use std::time::SystemTime;
 
 use crate::ecclient::AnswerResponse;
+use crate::submissionstore::Error;
+use crate::submissionstore::Log;
+use crate::submissionstore::PuzzleLogEntry;
+use crate::submissionstore::SubmissionAttempt;
+use crate::submissionstore::SubmissionStore;
+use crate::types::Part;
 use crate::types::PuzzleKey;
 
-const FILE: &str = "results.toml";
-
-#[derive(Debug, Default, Deserialize, Serialize)]
-struct PuzzleLogEntry {
-    rejected_answers: Vec<String>,
-    accepted_answer: Option<String>,
-    rejected_first_characters: Vec<String>,
-    rejected_answer_lengths: Vec<usize>,
-    correct_first_character: Option<String>,
-    correct_answer_length: Option<usize>,
-}
-
-#[derive(Debug, Default, Deserialize, Serialize)]
-struct Log {
-    answers: HashMap<String, PuzzleLogEntry>,
+/// Identifies the solver binary, so that attempts can be traced back to the code.
+fn solver_build() -> String {
+    match option_env!("EC_GIT_REVISION") {
+        Some(revision) => revision.to_string(),
+        None => format!("v{}", env!("CARGO_PKG_VERSION")),
+    }
 }
 
-fn puzzle_key_string(key: &PuzzleKey) -> String {
+/// Builds the key of a log entry, e.g. `user1337/event2024quest5part2`. The user is
+/// part of the key so that several accounts can share the same store.
+fn puzzle_key_string(user_id: i64, key: &PuzzleKey) -> String {
     format!(
-        "event{}quest{}part{}",
+        "user{}/event{}quest{}part{}",
+        user_id,
         key.event,
         key.quest,
         key.part.as_u8()
     )
 }
 
-fn read_submission_log() -> Option<Log> {
-    let log = fs::read_to_string(FILE).ok()?;
-    let log: Log = toml::from_str(log.as_str()).ok()?;
-    Some(log)
+/// Parses a key built by `puzzle_key_string` back into the user id and puzzle key.
+pub fn parse_puzzle_key_string(key: &str) -> Option<(i64, PuzzleKey)> {
+    let (user, key) = key.strip_prefix("user")?.split_once('/')?;
+    let (event, key) = key.strip_prefix("event")?.split_once("quest")?;
+    let (quest, part) = key.split_once("part")?;
+    Some((
+        user.parse().ok()?,
+        PuzzleKey {
+            event: event.parse().ok()?,
+            quest: quest.parse().ok()?,
+            part: part.parse().ok()?,
+        },
+    ))
+}
+
+/// Entries written before the log was keyed by user have no `user<id>/` prefix.
+fn is_legacy_key(key: &str) -> bool {
+    !key.contains('/')
 }
 
-fn write_submission_log(log: &Log) {
-    fs::write(FILE, toml::to_string_pretty(log).unwrap().as_str()).unwrap();
+/// Assigns entries without a user to the given user. Returns true if anything changed.
+/// Once the log holds entries of any user, it may be shared, so the legacy entries
+/// could be someone else's and are left alone.
+fn migrate_legacy_entries(log: &mut Log, user_id: i64) -> bool {
+    let (legacy_keys, user_keys): (Vec<String>, Vec<String>) =
+        log.answers.keys().cloned().partition(|k| is_legacy_key(k));
+    if legacy_keys.is_empty() {
+        return false;
+    }
+    if !user_keys.is_empty() {
+        log::warn!(
+            "not migrating {} legacy entries, the log already has entries keyed by user",
+            legacy_keys.len()
+        );
+        return false;
+    }
+    for legacy_key in &legacy_keys {
+        let entry = log.answers.remove(legacy_key).unwrap();
+        let key = format!("user{user_id}/{legacy_key}");
+        log::info!("migrating {legacy_key} to {key}");
+        log.answers.insert(key, entry);
+    }
+    true
+}
+
+/// Loads the log, returning None if it does not exist yet. The caller must hold the
+/// store lock, as legacy entries are migrated on the fly.
+fn read_submission_log(store: &dyn SubmissionStore, user_id: i64) -> Result<Option<Log>, Error> {
+    let Some(mut log) = store.load()? else {
+        return Ok(None);
+    };
+    if migrate_legacy_entries(&mut log, user_id) {
+        store.save(&log)?;
+    }
+    Ok(Some(log))
 }
 
 pub struct SubmissionResult {
@@ -51,7 +96,19 @@ pub struct SubmissionResult {
     pub cached: bool,
 }
 
-fn check_submission_log(key: &PuzzleKey, answer: &str) -> SubmissionResult {
+fn check_submission_log(
+    store: &dyn SubmissionStore,
+    user_id: i64,
+    key: &PuzzleKey,
+    answer: &str,
+) -> Result<SubmissionResult, Error> {
+    let _lock = store.lock()?;
+    let log = read_submission_log(store, user_id)?;
+    Ok(check_log(log.as_ref(), user_id, key, answer))
+}
+
+/// What the log tells about the answer without submitting it.
+fn check_log(log: Option<&Log>, user_id: i64, key: &PuzzleKey, answer: &str) -> SubmissionResult {
     let mut result = SubmissionResult {
         details: None,
         is_answer_correct: None,
@@ -59,12 +116,10 @@ fn check_submission_log(key: &PuzzleKey, answer: &str) -> SubmissionResult {
         is_length_correct: None,
         cached: true,
     };
-    let log = read_submission_log();
-    if log.is_none() {
+    let Some(log) = log else {
         return result;
-    }
-    let log = log.unwrap();
-    if let Some(entry) = log.answers.get(puzzle_key_string(key).as_str()) {
+    };
+    if let Some(entry) = log.answers.get(puzzle_key_string(user_id, key).as_str()) {
         if entry.rejected_answer_lengths.contains(&answer.len()) {
             result.is_answer_correct = Some(false);
             result.is_length_correct = Some(false);
@@ -93,15 +148,133 @@ fn check_submission_log(key: &PuzzleKey, answer: &str) -> SubmissionResult {
     result
 }
 
-fn record_submission_log(key: &PuzzleKey, answer: &str, result: &SubmissionResult) {
-    let mut log = read_submission_log()
-        .or_else(|| Some(Log::default()))
-        .unwrap();
-    let key = puzzle_key_string(key);
+/// Something suspicious about an answer, found without asking the server.
+#[derive(Debug, PartialEq)]
+pub enum AnswerProblem {
+    Empty,
+    MultiLine,
+    WhitespacePadded,
+    NonPrintable(char),
+    /// A numeric answer whose digit count differs from the known correct length.
+    LengthMismatch {
+        length: usize,
+        correct_length: usize,
+    },
+    /// The answer is the one already accepted for another part of the quest.
+    SameAsPart(u8),
+}
+
+impl AnswerProblem {
+    /// Rejected answers are certainly malformed. The rest only deserve a warning.
+    pub fn is_rejected(&self) -> bool {
+        matches!(
+            *self,
+            Self::Empty | Self::MultiLine | Self::WhitespacePadded | Self::NonPrintable(_)
+        )
+    }
+}
+
+impl fmt::Display for AnswerProblem {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Self::Empty => write!(f, "the answer is empty"),
+            Self::MultiLine => write!(f, "the answer spans several lines"),
+            Self::WhitespacePadded => write!(f, "the answer starts or ends with whitespace"),
+            Self::NonPrintable(c) => {
+                write!(f, "the answer contains a non-printable character {:?}", c)
+            }
+            Self::LengthMismatch {
+                length,
+                correct_length,
+            } => write!(
+                f,
+                "the answer has {} digits, but the correct answer is known to have {}",
+                length, correct_length
+            ),
+            Self::SameAsPart(part) => {
+                write!(f, "the answer was already accepted for part {}", part)
+            }
+        }
+    }
+}
+
+fn is_numeric(answer: &str) -> bool {
+    let digits = answer.strip_prefix('-').unwrap_or(answer);
+    !digits.is_empty() && digits.chars().all(|c| c.is_ascii_digit())
+}
+
+/// Runs cheap local sanity checks on the answer, using what the submission log
+/// knows about this and the other parts of the quest.
+pub fn validate_answer(
+    store: &dyn SubmissionStore,
+    user_id: i64,
+    key: &PuzzleKey,
+    answer: &str,
+) -> Result<Vec<AnswerProblem>, Error> {
+    let mut problems = vec![];
+    if answer.is_empty() {
+        problems.push(AnswerProblem::Empty);
+    }
+    if answer.contains(['\n', '\r']) {
+        problems.push(AnswerProblem::MultiLine);
+    }
+    if answer.trim() != answer {
+        problems.push(AnswerProblem::WhitespacePadded);
+    }
+    if let Some(c) = answer
+        .chars()
+        .find(|c| c.is_control() && !c.is_whitespace())
+    {
+        problems.push(AnswerProblem::NonPrintable(c));
+    }
+    let _lock = store.lock()?;
+    let Some(log) = read_submission_log(store, user_id)? else {
+        return Ok(problems);
+    };
+    if let Some(entry) = log.answers.get(puzzle_key_string(user_id, key).as_str())
+        && let Some(correct_length) = entry.correct_answer_length
+        && is_numeric(answer)
+        && answer.len() != correct_length
+    {
+        problems.push(AnswerProblem::LengthMismatch {
+            length: answer.len(),
+            correct_length,
+        });
+    }
+    for part in Part::ALL {
+        if part == key.part {
+            continue;
+        }
+        let other_key = PuzzleKey {
+            event: key.event,
+            quest: key.quest,
+            part,
+        };
+        if let Some(entry) = log
+            .answers
+            .get(puzzle_key_string(user_id, &other_key).as_str())
+            && entry.accepted_answer.as_deref() == Some(answer)
+        {
+            problems.push(AnswerProblem::SameAsPart(other_key.part.as_u8()));
+        }
+    }
+    Ok(problems)
+}
+
+fn record_in_log(
+    log: &mut Log,
+    user_id: i64,
+    key: &PuzzleKey,
+    answer: &str,
+    result: &SubmissionResult,
+    attempt: Option<SubmissionAttempt>,
+) {
+    let key = puzzle_key_string(user_id, key);
     if !log.answers.contains_key(&key) {
         log.answers.insert(key.clone(), PuzzleLogEntry::default());
     }
     let entry = log.answers.get_mut(&key).unwrap();
+    entry.attempts.extend(attempt);
     match result.is_answer_correct {
         Some(true) => {
             entry.accepted_answer = Some(answer.to_string());
@@ -111,15 +284,13 @@ fn record_submission_log(key: &PuzzleKey, answer: &str, result: &SubmissionResul
         Some(false) => {
             entry.rejected_answers.push(answer.to_string());
             match result.is_length_correct {
-                None => {}
-                Some(false) => {
-                    if !entry.rejected_answer_lengths.contains(&answer.len()) {
-                        entry.rejected_answer_lengths.push(answer.len());
-                    }
+                Some(false) if !entry.rejected_answer_lengths.contains(&answer.len()) => {
+                    entry.rejected_answer_lengths.push(answer.len());
                 }
                 Some(true) => {
                     entry.correct_answer_length = Some(answer.len());
                 }
+                _ => {}
             }
             match (result.is_first_character_correct, answer.chars().next()) {
                 (Some(false), Some(first_char)) => {
@@ -138,25 +309,428 @@ fn record_submission_log(key: &PuzzleKey, answer: &str, result: &SubmissionResul
         }
         _ => {}
     }
-    write_submission_log(&log);
 }
 
-pub fn submit_with_cache<F>(key: &PuzzleKey, answer: &str, submit_fn: F) -> SubmissionResult
+fn submit_with_cache<F>(
+    store: &dyn SubmissionStore,
+    user_id: i64,
+    key: &PuzzleKey,
+    answer: &str,
+    solve_duration: Option<Duration>,
+    submit_fn: F,
+) -> Result<SubmissionResult, Error>
 where
     F: FnOnce(&PuzzleKey, &str) -> AnswerResponse,
 {
-    let submission_log = check_submission_log(key, answer);
+    // Held until the outcome is recorded, so that another process cannot submit the
+    // same answer in the meantime.
+    let _lock = store.lock()?;
+    let mut log = read_submission_log(store, user_id)?;
+    let submission_log = check_log(log.as_ref(), user_id, key, answer);
     if submission_log.is_answer_correct.is_some() {
-        return submission_log;
+        return Ok(submission_log);
     }
+    let submitted_at = SystemTime::now();
     let result = submit_fn(key, answer);
     let submission_result = SubmissionResult {
         is_answer_correct: Some(result.correct),
         is_first_character_correct: Some(result.first_correct),
         is_length_correct: Some(result.length_correct),
-        details: Some(result),
+        details: Some(result.clone()),
         cached: false,
     };
-    record_submission_log(key, answer, &submission_result);
-    submission_result
+    let attempt = SubmissionAttempt {
+        answer: answer.to_string(),
+        submitted_at,
+        solver_build: solver_build(),
+        solve_duration,
+        response: result,
+    };
+    let log = log.get_or_insert_default();
+    record_in_log(log, user_id, key, answer, &submission_result, Some(attempt));
+    store.save(log)?;
+    Ok(submission_result)
+}
+
+/// Submits the candidates in order until one of them is accepted. Candidates which
+/// are known to be wrong from the log are not submitted. Returns the outcome for
+/// every candidate that was considered.
+pub fn submit_candidates<F>(
+    store: &dyn SubmissionStore,
+    user_id: i64,
+    key: &PuzzleKey,
+    candidates: &[String],
+    solve_duration: Option<Duration>,
+    mut submit_fn: F,
+) -> Result<Vec<(String, SubmissionResult)>, Error>
+where
+    F: FnMut(&PuzzleKey, &str) -> AnswerResponse,
+{
+    let mut results = vec![];
+    for candidate in candidates {
+        let mut check = check_submission_log(store, user_id, key, candidate)?;
+        if check.is_first_character_correct == Some(false) || check.is_length_correct == Some(false)
+        {
+            log::info!("candidate {candidate} conflicts with the known hints, skipping it");
+            check.is_answer_correct = Some(false);
+        }
+        let result = if check.is_answer_correct.is_some() {
+            check
+        } else {
+            submit_with_cache(
+                store,
+                user_id,
+                key,
+                candidate,
+                solve_duration,
+                &mut submit_fn,
+            )?
+        };
+        let accepted = result.is_answer_correct == Some(true);
+        results.push((candidate.clone(), result));
+        if accepted {
+            break;
+        }
+    }
+    Ok(results)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::sync::Mutex;
+
+    use super::*;
+    use crate::submissionstore::MemoryStore;
+    use crate::submissionstore::StoreLock;
+    use crate::submissionstore::TomlStore;
+    use crate::submissionstore::temp_dir;
+
+    fn key() -> PuzzleKey {
+        PuzzleKey {
+            event: 2024,
+            quest: 5,
+            part: Part::Two,
+        }
+    }
+
+    fn record_submission_log(
+        store: &dyn SubmissionStore,
+        user_id: i64,
+        key: &PuzzleKey,
+        answer: &str,
+        result: &SubmissionResult,
+        attempt: Option<SubmissionAttempt>,
+    ) -> Result<(), Error> {
+        let _lock = store.lock()?;
+        let mut log = read_submission_log(store, user_id)?.unwrap_or_default();
+        record_in_log(&mut log, user_id, key, answer, result, attempt);
+        store.save(&log)
+    }
+
+    fn rejected() -> SubmissionResult {
+        SubmissionResult {
+            is_answer_correct: Some(false),
+            is_first_character_correct: Some(true),
+            is_length_correct: Some(false),
+            details: None,
+            cached: false,
+        }
+    }
+
+    #[test]
+    fn test_missing_log_is_empty() {
+        let store = MemoryStore::default();
+        assert!(read_submission_log(&store, 1337).unwrap().is_none());
+        let result = check_submission_log(&store, 1337, &key(), "42").unwrap();
+        assert_eq!(None, result.is_answer_correct);
+    }
+
+    #[test]
+    fn test_unparseable_log_is_an_error() {
+        let path = temp_dir("autosubmit-unparseable").join("results.toml");
+        fs::write(&path, "[answers\nthis is not toml").unwrap();
+        let store = TomlStore::new(&path);
+        assert!(matches!(
+            check_submission_log(&store, 1337, &key(), "42"),
+            Err(Error::ParseError(_, _))
+        ));
+        assert!(matches!(
+            record_submission_log(&store, 1337, &key(), "42", &rejected(), None),
+            Err(Error::ParseError(_, _))
+        ));
+        let candidates = vec![String::from("42")];
+        assert!(matches!(
+            submit_candidates(&store, 1337, &key(), &candidates, None, |_, _| {
+                panic!("nothing should be submitted")
+            }),
+            Err(Error::ParseError(_, _))
+        ));
+        // The broken file must be left alone rather than replaced with an empty log.
+        assert_eq!(
+            "[answers\nthis is not toml",
+            fs::read_to_string(&path).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_record_then_check() {
+        let store = MemoryStore::default();
+        record_submission_log(&store, 1337, &key(), "123", &rejected(), None).unwrap();
+        let result = check_submission_log(&store, 1337, &key(), "123").unwrap();
+        assert_eq!(Some(false), result.is_answer_correct);
+        let result = check_submission_log(&store, 1337, &key(), "456").unwrap();
+        assert_eq!(Some(false), result.is_length_correct);
+        let result = check_submission_log(&store, 1337, &key(), "45").unwrap();
+        assert_eq!(None, result.is_answer_correct);
+        let result = check_submission_log(&store, 7, &key(), "123").unwrap();
+        assert_eq!(None, result.is_answer_correct);
+    }
+
+    fn accepted() -> SubmissionResult {
+        SubmissionResult {
+            is_answer_correct: Some(true),
+            is_first_character_correct: Some(true),
+            is_length_correct: Some(true),
+            details: None,
+            cached: false,
+        }
+    }
+
+    #[test]
+    fn test_validate_malformed_answers() {
+        let store = MemoryStore::default();
+        let validate = |answer| validate_answer(&store, 1337, &key(), answer).unwrap();
+        assert_eq!(vec![AnswerProblem::Empty], validate(""));
+        assert_eq!(vec![AnswerProblem::WhitespacePadded], validate(" 42"));
+        assert_eq!(
+            vec![AnswerProblem::MultiLine, AnswerProblem::WhitespacePadded],
+            validate("42\n")
+        );
+        assert_eq!(vec![AnswerProblem::MultiLine], validate("4\n2"));
+        assert_eq!(
+            vec![AnswerProblem::NonPrintable('\u{7}')],
+            validate("4\u{7}2")
+        );
+        assert!(validate("13 43").is_empty());
+        assert!(validate("-42").is_empty());
+        assert!(validate(" 42").iter().all(AnswerProblem::is_rejected));
+    }
+
+    #[test]
+    fn test_validate_against_history() {
+        let store = MemoryStore::default();
+        let part_one = PuzzleKey {
+            event: 2024,
+            quest: 5,
+            part: Part::One,
+        };
+        record_submission_log(&store, 1337, &part_one, "1234", &accepted(), None).unwrap();
+        record_submission_log(&store, 1337, &key(), "12345", &accepted(), None).unwrap();
+        let validate = |answer| validate_answer(&store, 1337, &key(), answer).unwrap();
+        assert_eq!(
+            vec![
+                AnswerProblem::LengthMismatch {
+                    length: 4,
+                    correct_length: 5
+                },
+                AnswerProblem::SameAsPart(1)
+            ],
+            validate("1234")
+        );
+        assert!(validate("12345").is_empty());
+        assert!(validate("abcd").is_empty());
+        assert!(!AnswerProblem::SameAsPart(1).is_rejected());
+        // Another user's history does not matter.
+        assert!(
+            validate_answer(&store, 7, &key(), "1234")
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    fn response(correct: bool, length_correct: bool, first_correct: bool) -> AnswerResponse {
+        AnswerResponse {
+            correct,
+            length_correct,
+            first_correct,
+            time: SystemTime::UNIX_EPOCH,
+            local_time: Duration::ZERO,
+            global_time: Duration::ZERO,
+            global_place: 0,
+            global_score: 0,
+        }
+    }
+
+    #[test]
+    fn test_submit_candidates_falls_through() {
+        let store = MemoryStore::default();
+        let candidates: Vec<String> = ["123", "456", "78", "14", "99", "71"]
+            .into_iter()
+            .map(String::from)
+            .collect();
+        let mut submitted = vec![];
+        let results = submit_candidates(&store, 1337, &key(), &candidates, None, |_, answer| {
+            submitted.push(answer.to_string());
+            match answer {
+                // Wrong length and first character: "456" and "14" are skipped.
+                "123" => response(false, false, false),
+                // Right length and first character: "99" is skipped.
+                "78" => response(false, true, true),
+                "71" => response(true, true, true),
+                _ => panic!("{answer} should not have been submitted"),
+            }
+        })
+        .unwrap();
+        assert_eq!(vec!["123", "78", "71"], submitted);
+        let outcomes: Vec<(&str, Option<bool>, bool)> = results
+            .iter()
+            .map(|(a, r)| (a.as_str(), r.is_answer_correct, r.cached))
+            .collect();
+        assert_eq!(
+            vec![
+                ("123", Some(false), false),
+                ("456", Some(false), true),
+                ("78", Some(false), false),
+                ("14", Some(false), true),
+                ("99", Some(false), true),
+                ("71", Some(true), false),
+            ],
+            outcomes
+        );
+        let entry = &read_submission_log(&store, 1337).unwrap().unwrap().answers["user1337/event2024quest5part2"];
+        assert_eq!(3, entry.attempts.len());
+    }
+
+    #[test]
+    fn test_submit_candidates_stops_at_known_answer() {
+        let store = MemoryStore::default();
+        record_submission_log(&store, 1337, &key(), "42", &accepted(), None).unwrap();
+        let candidates = vec![String::from("42"), String::from("43")];
+        let results = submit_candidates(&store, 1337, &key(), &candidates, None, |_, _| {
+            panic!("nothing should be submitted")
+        })
+        .unwrap();
+        assert_eq!(1, results.len());
+        assert!(results[0].1.cached);
+    }
+
+    /// A store whose lock can be checked from within a submission.
+    #[derive(Default)]
+    struct WatchedStore {
+        inner: MemoryStore,
+        lock: Mutex<()>,
+    }
+
+    impl SubmissionStore for WatchedStore {
+        fn load(&self) -> Result<Option<Log>, Error> {
+            self.inner.load()
+        }
+
+        fn save(&self, log: &Log) -> Result<(), Error> {
+            self.inner.save(log)
+        }
+
+        fn lock(&self) -> Result<StoreLock<'_>, Error> {
+            Ok(StoreLock::Memory {
+                _guard: self.lock.lock().unwrap(),
+            })
+        }
+    }
+
+    #[test]
+    fn test_store_is_locked_while_submitting() {
+        let store = WatchedStore::default();
+        let candidates = vec![String::from("42")];
+        submit_candidates(&store, 1337, &key(), &candidates, None, |_, _| {
+            assert!(store.lock.try_lock().is_err(), "the store is not locked");
+            response(true, true, true)
+        })
+        .unwrap();
+        assert!(store.lock.try_lock().is_ok());
+        let result = check_submission_log(&store, 1337, &key(), "42").unwrap();
+        assert_eq!(Some(true), result.is_answer_correct);
+    }
+
+    #[test]
+    fn test_puzzle_key_string() {
+        assert_eq!(
+            "user1337/event2024quest5part2",
+            puzzle_key_string(
+                1337,
+                &PuzzleKey {
+                    event: 2024,
+                    quest: 5,
+                    part: Part::Two,
+                }
+            )
+        );
+    }
+
+    #[test]
+    fn test_legacy_entries_are_migrated_in_store() {
+        let store = MemoryStore::default();
+        let mut log = Log::default();
+        log.answers.insert(
+            String::from("event2024quest5part2"),
+            PuzzleLogEntry {
+                rejected_answers: vec![String::from("41")],
+                ..Default::default()
+            },
+        );
+        store.save(&log).unwrap();
+        let result = check_submission_log(&store, 1337, &key(), "41").unwrap();
+        assert_eq!(Some(false), result.is_answer_correct);
+        let log = store.load().unwrap().unwrap();
+        assert!(log.answers.contains_key("user1337/event2024quest5part2"));
+        assert!(!log.answers.contains_key("event2024quest5part2"));
+    }
+
+    #[test]
+    fn test_parse_puzzle_key_string() {
+        let (user_id, key) = parse_puzzle_key_string("user1337/event2024quest5part2").unwrap();
+        assert_eq!(1337, user_id);
+        assert_eq!(
+            "user1337/event2024quest5part2",
+            puzzle_key_string(user_id, &key)
+        );
+        assert!(parse_puzzle_key_string("event2024quest5part2").is_none());
+        assert!(parse_puzzle_key_string("user1337/event2024quest5part4").is_none());
+    }
+
+    #[test]
+    fn test_migrate_legacy_entries() {
+        let mut log = Log::default();
+        log.answers.insert(
+            String::from("event2024quest5part2"),
+            PuzzleLogEntry {
+                accepted_answer: Some(String::from("42")),
+                ..Default::default()
+            },
+        );
+        assert!(migrate_legacy_entries(&mut log, 1337));
+        assert_eq!(1, log.answers.len());
+        assert_eq!(
+            Some(String::from("42")),
+            log.answers["user1337/event2024quest5part2"].accepted_answer
+        );
+        assert!(!migrate_legacy_entries(&mut log, 1337));
+    }
+
+    #[test]
+    fn test_legacy_entries_of_shared_log_are_left_alone() {
+        let mut log = Log::default();
+        log.answers.insert(
+            String::from("event2024quest5part2"),
+            PuzzleLogEntry::default(),
+        );
+        log.answers.insert(
+            String::from("user7/event2024quest5part1"),
+            PuzzleLogEntry::default(),
+        );
+        assert!(!migrate_legacy_entries(&mut log, 1337));
+        assert_eq!(2, log.answers.len());
+        assert!(log.answers.contains_key("event2024quest5part2"));
+        assert!(!log.answers.contains_key("user1337/event2024quest5part2"));
+    }
 }
@@ -0,0 +1,245 @@
+use std::cell::Cell;
+use std::fmt;
+use std::panic;
+use std::sync::Mutex;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+use std::time::Instant;
+
+use crate::progress::Snapshot;
+
+/// Why a computation was stopped.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Reason {
+    Timeout,
+    Interrupted,
+}
+
+impl fmt::Display for Reason {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Reason::Timeout => write!(f, "timeout"),
+            Reason::Interrupted => write!(f, "interrupted"),
+        }
+    }
+}
+
+/// The result of a cancelled computation: why and when it stopped, and how far it
+/// got, if it was stopped by a progress counter.
+#[derive(Debug)]
+pub struct Cancelled {
+    pub reason: Reason,
+    pub elapsed: Duration,
+    pub progress: Option<Snapshot>,
+}
+
+impl fmt::Display for Cancelled {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "cancelled after {} s ({})",
+            self.elapsed.as_secs(),
+            self.reason
+        )?;
+        if let Some(ref progress) = self.progress {
+            write!(f, ", at {}", progress.render_line())?;
+        }
+        Ok(())
+    }
+}
+
+/// Tells a solver when to give up: at a deadline, or when the user presses Ctrl-C.
+#[derive(Clone, Copy, Debug)]
+pub struct CancelToken {
+    started: Instant,
+    deadline: Option<Instant>,
+}
+
+impl CancelToken {
+    pub fn new(timeout: Option<Duration>) -> CancelToken {
+        let started = Instant::now();
+        CancelToken {
+            started,
+            deadline: timeout.map(|timeout| started + timeout),
+        }
+    }
+
+    pub fn reason(&self) -> Option<Reason> {
+        if INTERRUPTED.load(Ordering::Relaxed) {
+            Some(Reason::Interrupted)
+        } else if self
+            .deadline
+            .is_some_and(|deadline| Instant::now() >= deadline)
+        {
+            Some(Reason::Timeout)
+        } else {
+            None
+        }
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.reason().is_some()
+    }
+
+    /// The time left until the deadline, if there is one.
+    pub fn remaining(&self) -> Option<Duration> {
+        self.deadline
+            .map(|deadline| deadline.saturating_duration_since(Instant::now()))
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.started.elapsed()
+    }
+}
+
+thread_local! {
+    /// The token of the solver running on this thread.
+    static CURRENT: Cell<Option<CancelToken>> = const { Cell::new(None) };
+}
+
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+/// Installs the token which solvers on this thread check from now on, and forgets an
+/// earlier Ctrl-C.
+pub fn start(timeout: Option<Duration>) -> CancelToken {
+    let token = CancelToken::new(timeout);
+    INTERRUPTED.store(false, Ordering::Relaxed);
+    CURRENT.set(Some(token));
+    token
+}
+
+/// The token of the solver running now; one which never expires if none was started.
+pub fn current() -> CancelToken {
+    CURRENT.get().unwrap_or_else(|| CancelToken::new(None))
+}
+
+/// Stops the solver by unwinding out of it, if it has been cancelled. `run` catches
+/// the unwinding and turns it into a `Cancelled` result.
+pub fn checkpoint() {
+    let token = current();
+    if let Some(reason) = token.reason() {
+        stop(Cancelled {
+            reason,
+            elapsed: token.elapsed(),
+            progress: None,
+        });
+    }
+}
+
+/// Stops the solver by unwinding out of it.
+pub fn stop(cancelled: Cancelled) -> ! {
+    // Unlike panic!, resume_unwind does not print a panic message.
+    panic::resume_unwind(Box::new(cancelled))
+}
+
+/// Runs the solver with a fresh token, returning how it was cancelled if it was.
+/// Ctrl-C cancels the solver while it runs. Panics other than cancellation carry on
+/// unwinding.
+pub fn run<T>(timeout: Option<Duration>, solver: impl FnOnce() -> T) -> Result<T, Cancelled> {
+    let _interrupts = InterruptHandler::install();
+    start(timeout);
+    let result = panic::catch_unwind(panic::AssertUnwindSafe(solver));
+    CURRENT.set(None);
+    match result {
+        Ok(value) => Ok(value),
+        Err(payload) => match payload.downcast::<Cancelled>() {
+            Ok(cancelled) => Err(*cancelled),
+            Err(payload) => panic::resume_unwind(payload),
+        },
+    }
+}
+
+extern "C" fn on_interrupt(_: libc::c_int) {
+    INTERRUPTED.store(true, Ordering::Relaxed);
+    // A second Ctrl-C kills the process as usual, should the solver not stop.
+    unsafe {
+        libc::signal(libc::SIGINT, libc::SIG_DFL);
+    }
+}
+
+/// How many solvers are running, and the Ctrl-C handler from before the first one.
+static RUNNING: Mutex<(usize, libc::sighandler_t)> = Mutex::new((0, libc::SIG_DFL));
+
+/// Makes Ctrl-C cancel the running solvers instead of killing the process, until the
+/// last one is dropped.
+struct InterruptHandler;
+
+impl InterruptHandler {
+    fn install() -> InterruptHandler {
+        let mut running = RUNNING.lock().unwrap();
+        // Armed again for every solver, as a Ctrl-C during an earlier one disarms it.
+        let previous = unsafe {
+            libc::signal(
+                libc::SIGINT,
+                on_interrupt as extern "C" fn(libc::c_int) as libc::sighandler_t,
+            )
+        };
+        if running.0 == 0 {
+            running.1 = previous;
+        }
+        running.0 += 1;
+        InterruptHandler
+    }
+}
+
+impl Drop for InterruptHandler {
+    fn drop(&mut self) {
+        let mut running = RUNNING.lock().unwrap();
+        running.0 -= 1;
+        if running.0 == 0 {
+            unsafe {
+                libc::signal(libc::SIGINT, running.1);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_run() {
+        assert_eq!(3, run(None, || 1 + 2).unwrap());
+        let cancelled = run(Some(Duration::ZERO), || {
+            checkpoint();
+            unreachable!("the deadline has passed")
+        })
+        .unwrap_err();
+        assert_eq!(Reason::Timeout, cancelled.reason);
+        assert_eq!("cancelled after 0 s (timeout)", cancelled.to_string());
+        assert!(!current().is_cancelled());
+    }
+
+    fn interrupt_handler() -> libc::sighandler_t {
+        unsafe {
+            let mut action: libc::sigaction = std::mem::zeroed();
+            libc::sigaction(libc::SIGINT, std::ptr::null(), &mut action);
+            action.sa_sigaction
+        }
+    }
+
+    #[test]
+    fn test_run_handles_interrupts() {
+        let handler = on_interrupt as extern "C" fn(libc::c_int) as libc::sighandler_t;
+        run(None, || {
+            assert_eq!(handler, interrupt_handler());
+            // As after a first Ctrl-C.
+            unsafe {
+                libc::signal(libc::SIGINT, libc::SIG_DFL);
+            }
+        })
+        .unwrap();
+        // The next solver can be interrupted again.
+        run(None, || assert_eq!(handler, interrupt_handler())).unwrap();
+    }
+
+    #[test]
+    fn test_remaining() {
+        let token = CancelToken::new(Some(Duration::from_secs(60)));
+        assert!(token.remaining().unwrap() > Duration::from_secs(59));
+        assert!(!token.is_cancelled());
+        assert_eq!(None, CancelToken::new(None).remaining());
+    }
+}
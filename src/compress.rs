@@ -0,0 +1,247 @@
+use crate::geometry::Dir4;
+use crate::geometry::Point;
+use crate::search;
+use crate::search::Path;
+
+/// An axis-aligned wall covering the cells from `min` to `max`, both included.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Segment {
+    pub min: Point<i64>,
+    pub max: Point<i64>,
+}
+
+impl Segment {
+    /// The segment between two cells in the same row or column, in any order.
+    pub fn new(a: Point<i64>, b: Point<i64>) -> Segment {
+        Segment {
+            min: Point::new(a.x.min(b.x), a.y.min(b.y)),
+            max: Point::new(a.x.max(b.x), a.y.max(b.y)),
+        }
+    }
+
+    pub fn intersects(&self, other: &Segment) -> bool {
+        self.min.x <= other.max.x
+            && other.min.x <= self.max.x
+            && self.min.y <= other.max.y
+            && other.min.y <= self.max.y
+    }
+}
+
+/// A coarse grid over a huge map with a few walls. Its lines run through the points
+/// of interest and along both sides and ends of every wall, so that a shortest path
+/// between the points only turns where two lines cross. The crossings are the nodes
+/// of the grid, indexed by (column, row).
+pub struct CompressedGrid {
+    xs: Vec<i64>,
+    ys: Vec<i64>,
+    walls: Vec<Segment>,
+}
+
+impl CompressedGrid {
+    pub fn new(
+        walls: Vec<Segment>,
+        points: impl IntoIterator<Item = Point<i64>>,
+    ) -> CompressedGrid {
+        let (mut xs, mut ys) = (vec![], vec![]);
+        for wall in &walls {
+            xs.extend([wall.min.x - 1, wall.max.x + 1]);
+            ys.extend([wall.min.y - 1, wall.max.y + 1]);
+        }
+        for point in points {
+            xs.push(point.x);
+            ys.push(point.y);
+        }
+        for coordinates in [&mut xs, &mut ys] {
+            coordinates.sort();
+            coordinates.dedup();
+            // A line around everything to walk around the outermost walls.
+            if let (Some(&first), Some(&last)) = (coordinates.first(), coordinates.last()) {
+                coordinates.insert(0, first - 1);
+                coordinates.push(last + 1);
+            }
+        }
+        CompressedGrid { xs, ys, walls }
+    }
+
+    /// The number of columns and rows.
+    pub fn size(&self) -> Point<usize> {
+        Point::new(self.xs.len(), self.ys.len())
+    }
+
+    /// The real coordinates of a node.
+    pub fn real(&self, node: Point<usize>) -> Point<i64> {
+        Point::new(self.xs[node.x], self.ys[node.y])
+    }
+
+    /// The node at the real coordinates, if a line runs through them both ways.
+    pub fn node(&self, point: Point<i64>) -> Option<Point<usize>> {
+        Some(Point::new(
+            self.xs.binary_search(&point.x).ok()?,
+            self.ys.binary_search(&point.y).ok()?,
+        ))
+    }
+
+    /// Whether walking straight from one point to the other hits a wall, not counting
+    /// the point it starts from.
+    pub fn blocked(&self, from: Point<i64>, to: Point<i64>) -> bool {
+        let step = Point::new((to.x - from.x).signum(), (to.y - from.y).signum());
+        let walk = Segment::new(from + step, to);
+        self.walls.iter().any(|wall| wall.intersects(&walk))
+    }
+
+    /// The adjacent nodes which can be walked to, with the real length of the walk.
+    pub fn neighbours(&self, node: Point<usize>) -> Vec<(Point<usize>, i64)> {
+        let from = self.real(node);
+        Dir4::ALL
+            .into_iter()
+            .filter_map(|dir| node.step_within(dir, self.size()))
+            .map(|next| (next, self.real(next)))
+            .filter(|&(_, to)| !self.blocked(from, to))
+            .map(|(next, to)| (next, from.manhattan(to)))
+            .collect()
+    }
+
+    /// A shortest walk between two points which were given to `new`, as the real
+    /// coordinates of the nodes where it may turn.
+    pub fn shortest_path(&self, from: Point<i64>, to: Point<i64>) -> Option<Path<Point<i64>, i64>> {
+        let (start, goal) = (self.node(from)?, self.node(to)?);
+        let path = search::astar(
+            [start],
+            |&node| self.neighbours(node),
+            |&node| self.real(node).manhattan(to),
+            |&node| node == goal,
+        )?;
+        Some(Path {
+            cost: path.cost,
+            states: path
+                .states
+                .into_iter()
+                .map(|node| self.real(node))
+                .collect(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::search::bfs;
+
+    /// The length of a shortest walk found cell by cell, within a margin around the
+    /// walls.
+    fn walk_cell_by_cell(walls: &[Segment], from: Point<i64>, to: Point<i64>) -> Option<usize> {
+        let corners = walls.iter().flat_map(|wall| [wall.min, wall.max]);
+        let (mut min, mut max) = (from, from);
+        for point in corners.chain([to]) {
+            min = Point::new(min.x.min(point.x), min.y.min(point.y));
+            max = Point::new(max.x.max(point.x), max.y.max(point.y));
+        }
+        let (min, max) = (min - Point::new(2, 2), max + Point::new(2, 2));
+        let open = |point: &Point<i64>| {
+            (min.x..=max.x).contains(&point.x)
+                && (min.y..=max.y).contains(&point.y)
+                && !walls
+                    .iter()
+                    .any(|wall| wall.intersects(&Segment::new(*point, *point)))
+        };
+        bfs(
+            [from],
+            |&point| {
+                Dir4::ALL
+                    .map(|dir| point + dir.delta())
+                    .into_iter()
+                    .filter(open)
+                    .collect::<Vec<_>>()
+            },
+            |&point| point == to,
+        )
+        .map(|path| path.cost)
+    }
+
+    fn wall(a: (i64, i64), b: (i64, i64)) -> Segment {
+        Segment::new(a.into(), b.into())
+    }
+
+    #[test]
+    fn test_segment() {
+        let horizontal = wall((5, 2), (1, 2));
+        assert_eq!(Point::new(1, 2), horizontal.min);
+        assert_eq!(Point::new(5, 2), horizontal.max);
+        assert!(horizontal.intersects(&wall((3, 0), (3, 9))));
+        assert!(horizontal.intersects(&wall((5, 2), (5, 2))));
+        assert!(!horizontal.intersects(&wall((6, 0), (6, 9))));
+        assert!(!horizontal.intersects(&wall((0, 3), (9, 3))));
+    }
+
+    #[test]
+    fn test_grid() {
+        let grid = CompressedGrid::new(vec![wall((0, 1), (100, 1))], [Point::new(50, 0)]);
+        assert_eq!(Point::new(5, 4), grid.size());
+        assert_eq!(Some(Point::new(2, 1)), grid.node(Point::new(50, 0)));
+        assert_eq!(Point::new(50, 0), grid.real(Point::new(2, 1)));
+        assert_eq!(None, grid.node(Point::new(50, 1)));
+        assert!(grid.blocked(Point::new(50, 0), Point::new(50, 2)));
+        assert!(!grid.blocked(Point::new(50, 0), Point::new(101, 0)));
+        assert_eq!(
+            vec![
+                (Point::new(2, 0), 1),
+                (Point::new(3, 1), 51),
+                (Point::new(1, 1), 51)
+            ],
+            grid.neighbours(Point::new(2, 1))
+        );
+    }
+
+    #[test]
+    fn test_shortest_path() {
+        // A cup open at the top, to be walked out of and around.
+        let walls = vec![
+            wall((0, 0), (0, 10)),
+            wall((0, 10), (8, 10)),
+            wall((8, 0), (8, 10)),
+        ];
+        let (from, to) = (Point::new(4, 5), Point::new(4, 12));
+        let grid = CompressedGrid::new(walls.clone(), [from, to]);
+        let path = grid.shortest_path(from, to).unwrap();
+        assert_eq!(
+            walk_cell_by_cell(&walls, from, to),
+            Some(path.cost as usize)
+        );
+        // Up and out of the cup, around a side and down below it.
+        assert_eq!(6 + 5 + 13 + 5, path.cost);
+        assert_eq!(Some(&from), path.states.first());
+        assert_eq!(Some(&to), path.states.last());
+        assert_eq!(None, grid.shortest_path(from, Point::new(1, 1)));
+    }
+
+    #[test]
+    fn test_shortest_path_matches_walking_cell_by_cell() {
+        let mazes = [
+            vec![wall((-3, 2), (3, 2)), wall((3, -4), (3, 2))],
+            vec![
+                wall((1, -5), (1, 5)),
+                wall((-5, 0), (0, 0)),
+                wall((2, 3), (6, 3)),
+            ],
+            vec![
+                wall((-2, -2), (2, -2)),
+                wall((2, -2), (2, 2)),
+                wall((-2, 2), (1, 2)),
+                wall((-2, -1), (-2, 2)),
+            ],
+        ];
+        let points = [(0, 0), (5, 5), (-4, 3), (0, 6)].map(Point::from);
+        for walls in mazes {
+            for from in points {
+                for to in points {
+                    let grid = CompressedGrid::new(walls.clone(), [from, to]);
+                    assert_eq!(
+                        walk_cell_by_cell(&walls, from, to),
+                        grid.shortest_path(from, to).map(|path| path.cost as usize),
+                        "from {from:?} to {to:?} among {walls:?}"
+                    );
+                }
+            }
+        }
+    }
+}
@@ -2,9 +2,14 @@ use std::error;
 use std::fmt;
 use std::string::FromUtf8Error;
 use std::sync::Arc;
+use std::sync::Mutex;
+use std::sync::MutexGuard;
 use std::sync::RwLock;
+use std::thread;
 use std::time::Duration;
+use std::time::Instant;
 use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
 
 use aes::cipher::BlockDecryptMut;
 use aes::cipher::KeyIvInit;
@@ -22,21 +27,51 @@ use serde::Serialize;
 use typenum::U16;
 use typenum::U32;
 
+use crate::logging;
 use crate::types::Part;
 use crate::types::PuzzleKey;
 
+/// The Everybody Codes session cookie. Its Debug and Display implementations never
+/// reveal the value, so it cannot end up in logs by accident.
+#[derive(Clone)]
+pub struct SessionCookie(String);
+
+impl SessionCookie {
+    pub fn new(value: &str) -> SessionCookie {
+        logging::register_secret(value);
+        SessionCookie(value.to_string())
+    }
+
+    /// Returns the actual cookie value. Only use this to build the request header.
+    fn expose(&self) -> &str {
+        self.0.as_str()
+    }
+}
+
+impl fmt::Debug for SessionCookie {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "SessionCookie([redacted])")
+    }
+}
+
+impl fmt::Display for SessionCookie {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "[redacted]")
+    }
+}
+
 /// Implements a CookieStore for the sole purpose of transmitting the Everybody Codes
 /// session cookie. Will not store any other cookies.
 struct EcSessionCookieStore {
     // Needs to be Arc<RwLock<_>> because CookieStore must implement Send and Sync
     // https://docs.rs/reqwest/latest/reqwest/cookie/trait.CookieStore.html
-    cookie: Arc<RwLock<String>>,
+    cookie: Arc<RwLock<SessionCookie>>,
 }
 
 impl EcSessionCookieStore {
-    fn new(cookie: &str) -> EcSessionCookieStore {
+    fn new(cookie: SessionCookie) -> EcSessionCookieStore {
         EcSessionCookieStore {
-            cookie: Arc::new(cookie.to_string().into()),
+            cookie: Arc::new(cookie.into()),
         }
     }
 }
@@ -45,9 +80,12 @@ impl reqwest::cookie::CookieStore for EcSessionCookieStore {
     fn set_cookies(&self, _cookie_headers: &mut dyn Iterator<Item = &HeaderValue>, _url: &Url) {}
     fn cookies(&self, _: &Url) -> Option<HeaderValue> {
         match HeaderValue::from_str(
-            format!("everybody-codes={}", self.cookie.read().unwrap()).as_str(),
+            format!("everybody-codes={}", self.cookie.read().unwrap().expose()).as_str(),
         ) {
-            Ok(hv) => Some(hv),
+            Ok(mut hv) => {
+                hv.set_sensitive(true);
+                Some(hv)
+            }
             Err(e) => {
                 error!("failed to create HeaderValue from cookie string: {e}");
                 None
@@ -128,14 +166,65 @@ impl From<url::ParseError> for Error {
     }
 }
 
+/// How long the penalty reported by api/user/me is trusted before asking again.
+const USER_INFO_TTL: Duration = Duration::from_secs(30);
+
+fn unix_millis(time: SystemTime) -> i64 {
+    match time.duration_since(UNIX_EPOCH) {
+        Ok(d) => d.as_millis() as i64,
+        Err(e) => -(e.duration().as_millis() as i64),
+    }
+}
+
+/// What we know about the server clock and the submission penalty as of the last
+/// api/user/me request.
+struct ServerClock {
+    /// Server time minus local time, in milliseconds.
+    offset_ms: i64,
+    penalty_until_ms: i64,
+    /// None if the penalty is known to be outdated, e.g. after a wrong answer.
+    fetched_at: Option<Instant>,
+}
+
+impl ServerClock {
+    fn measure(sent: SystemTime, received: SystemTime, me: &UserInfoResponse) -> ServerClock {
+        // Assume the server looked at its clock halfway through the round trip.
+        let local_ms = (unix_millis(sent) + unix_millis(received)) / 2;
+        ServerClock {
+            offset_ms: me.server_time_ms - local_ms,
+            penalty_until_ms: me.penalty_until_ms,
+            fetched_at: Some(Instant::now()),
+        }
+    }
+
+    fn is_fresh(&self) -> bool {
+        self.fetched_at
+            .is_some_and(|fetched_at| fetched_at.elapsed() < USER_INFO_TTL)
+    }
+
+    fn remaining_penalty(&self) -> Option<Duration> {
+        let server_now_ms = unix_millis(SystemTime::now()) + self.offset_ms;
+        match self.penalty_until_ms - server_now_ms {
+            ..=0 => None,
+            delay_ms => Some(Duration::from_millis(delay_ms as u64)),
+        }
+    }
+}
+
 pub struct EcClient {
     base_url: String,
     client: reqwest::blocking::Client,
+    user_id: i64,
     seed: i64,
+    clock: Mutex<ServerClock>,
+    // Held while waiting out the penalty and posting, so that submissions from
+    // several threads go out one by one.
+    submission_queue: Mutex<()>,
 }
 
 #[derive(Deserialize)]
 struct UserInfoResponse {
+    id: i64,
     #[serde(rename = "penaltyUntil")]
     penalty_until_ms: i64,
     #[serde(rename = "serverTime")]
@@ -165,7 +254,7 @@ struct AnswerRequest {
     answer: String,
 }
 
-#[derive(Debug, Deserialize, PartialEq)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct AnswerResponse {
     pub correct: bool,
     #[serde(rename = "lengthCorrect")]
@@ -186,17 +275,26 @@ pub struct AnswerResponse {
     pub global_score: i32,
 }
 
-fn get_me(base_url: &str, client: &Client) -> Result<UserInfoResponse, Error> {
+fn get_me(base_url: &str, client: &Client) -> Result<(UserInfoResponse, ServerClock), Error> {
     let url = format!("{}{}", base_url, "api/user/me");
     trace!("getting user information from {url}");
+    let sent = SystemTime::now();
     let response = client.get(url).send()?;
     response.error_for_status_ref()?;
     let response: UserInfoResponse = response.json()?;
-    Ok(response)
+    let clock = ServerClock::measure(sent, SystemTime::now(), &response);
+    trace!("server clock offset is {} ms", clock.offset_ms);
+    Ok((response, clock))
 }
 
 impl EcClient {
-    pub fn new_with_base(base_url: &str, cookie: &str) -> Result<EcClient, Error> {
+    /// Creates a client talking to the given EC instance. Verbose connection logging
+    /// dumps raw request bytes at trace level, so it is only enabled on request.
+    pub fn new_with_base(
+        base_url: &str,
+        cookie: SessionCookie,
+        connection_verbose: bool,
+    ) -> Result<EcClient, Error> {
         // We need to use an Arc here because reqwest::ClientBuilder requires an
         // Arc<C> of CookieStore:
         // https://docs.rs/reqwest/latest/reqwest/blocking/struct.ClientBuilder.html
@@ -204,18 +302,26 @@ impl EcClient {
         let client = reqwest::blocking::ClientBuilder::new()
             .user_agent("ec2024")
             .cookie_provider(cookie_store.clone())
-            .connection_verbose(true)
+            .connection_verbose(connection_verbose)
             .build()?;
-        let me = get_me(base_url, &client)?;
+        let (me, clock) = get_me(base_url, &client)?;
         Ok(EcClient {
             base_url: String::from(base_url),
             client,
+            user_id: me.id,
             seed: me.seed,
+            clock: Mutex::new(clock),
+            submission_queue: Mutex::new(()),
         })
     }
 
-    pub fn new(cookie: &str) -> Result<EcClient, Error> {
-        Self::new_with_base("https://everybody.codes/", cookie)
+    pub fn new(cookie: SessionCookie, connection_verbose: bool) -> Result<EcClient, Error> {
+        Self::new_with_base("https://everybody.codes/", cookie, connection_verbose)
+    }
+
+    /// The id of the account the session cookie belongs to.
+    pub fn user_id(&self) -> i64 {
+        self.user_id
     }
 
     fn get_encryption_key(&self, key: &PuzzleKey) -> Result<KeyResponse, Error> {
@@ -285,19 +391,45 @@ impl EcClient {
         }
     }
 
+    /// Returns the server clock, asking the server again only if the cached state is
+    /// too old or known to be outdated.
+    fn server_clock(&self) -> Result<MutexGuard<'_, ServerClock>, Error> {
+        let mut clock = self.clock.lock().unwrap();
+        if !clock.is_fresh() {
+            let (_, fresh_clock) = get_me(&self.base_url, &self.client)?;
+            *clock = fresh_clock;
+        }
+        Ok(clock)
+    }
+
+    /// Returns how long we still have to wait before the server accepts an answer,
+    /// corrected for the difference between the local and the server clock.
     pub fn get_penalty_delay(&self) -> Result<Option<Duration>, Error> {
-        let me = get_me(&self.base_url, &self.client)?;
-        match me.penalty_until_ms - me.server_time_ms {
-            ..=0 => Ok(None),
-            delay_ms => Ok(Some(Duration::from_millis(delay_ms as u64))),
+        Ok(self.server_clock()?.remaining_penalty())
+    }
+
+    /// Waits until the submission penalty expires, then posts the answer.
+    pub fn post_answer_when_allowed(
+        &self,
+        key: &PuzzleKey,
+        answer: &str,
+    ) -> Result<AnswerResponse, Error> {
+        let _queue = self.submission_queue.lock().unwrap();
+        while let Some(delay) = self.get_penalty_delay()? {
+            trace!("waiting {delay:?} for the penalty to expire");
+            thread::sleep(delay);
+        }
+        let response = self.post_answer(key, answer)?;
+        if !response.correct {
+            // A wrong answer starts a new penalty, which we only learn from the server.
+            self.clock.lock().unwrap().fetched_at = None;
         }
+        Ok(response)
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use std::time::UNIX_EPOCH;
-
     use httptest::Expectation;
     use httptest::Server;
     use httptest::ServerPool;
@@ -329,9 +461,40 @@ mod tests {
         server.expect(Expectation::matching(m).respond_with(status_code(200).body(r#"{"id":1337,"code":"DEADB33F","name":"johnny","country":"ua","url":"https://everybody.codes","level":15,"seed":7,"penaltyUntil":1755087853695,"badges":{"1":null,"2024":null,"2025":null},"ai":false,"streamer":false,"serverTime":1755113738573}"#)));
     }
 
+    fn set_penalty_expect(server: &Server, penalty_ms: i64) {
+        set_penalty_expect_times(server, penalty_ms, 1);
+    }
+
+    fn set_penalty_expect_times(server: &Server, penalty_ms: i64, times: usize) {
+        let m = all_of![
+            request::method("GET"),
+            request::path(matches("/api/user/me")),
+            request::headers(contains(("cookie", "everybody-codes=deadbeef"))),
+        ];
+        let server_time_ms = 1755113738573i64;
+        server.expect(
+            Expectation::matching(m)
+                .times(times)
+                .respond_with(status_code(200).body(format!(
+                    r#"{{"id":1337,"seed":7,"penaltyUntil":{},"serverTime":{}}}"#,
+                    server_time_ms + penalty_ms,
+                    server_time_ms
+                ))),
+        );
+    }
+
     fn make_client(server: &Server) -> EcClient {
-        let base_url = server_url(&server);
-        EcClient::new_with_base(base_url.as_str(), "deadbeef").expect("creating EC client")
+        let base_url = server_url(server);
+        EcClient::new_with_base(base_url.as_str(), SessionCookie::new("deadbeef"), false)
+            .expect("creating EC client")
+    }
+
+    #[test]
+    fn test_session_cookie_is_redacted() {
+        let cookie = SessionCookie::new("deadbeef");
+        assert_eq!("SessionCookie([redacted])", format!("{cookie:?}"));
+        assert_eq!("[redacted]", format!("{cookie}"));
+        assert_eq!("[redacted]", logging::redact("deadbeef"));
     }
 
     #[test]
@@ -341,6 +504,84 @@ mod tests {
         make_client(&server);
     }
 
+    #[test]
+    fn test_user_id() {
+        let server = SERVER_POOL.get_server();
+        set_base_expect(&server);
+        assert_eq!(1337, make_client(&server).user_id());
+    }
+
+    #[test]
+    fn test_get_penalty_delay_none() {
+        let server = SERVER_POOL.get_server();
+        set_base_expect(&server);
+        let client = make_client(&server);
+        assert_eq!(None, client.get_penalty_delay().unwrap());
+    }
+
+    #[test]
+    fn test_get_penalty_delay_is_cached() {
+        let server = SERVER_POOL.get_server();
+        set_penalty_expect(&server, 60_000);
+        let client = make_client(&server);
+        // The test server clock is far behind ours, which must not affect the delay.
+        let delay = client.get_penalty_delay().unwrap().unwrap();
+        assert!(delay > Duration::from_secs(55) && delay <= Duration::from_secs(60));
+        let delay = client.get_penalty_delay().unwrap().unwrap();
+        assert!(delay > Duration::from_secs(55) && delay <= Duration::from_secs(60));
+    }
+
+    #[test]
+    fn test_post_answer_when_allowed_waits_for_penalty() {
+        let server = SERVER_POOL.get_server();
+        set_penalty_expect(&server, 300);
+        let m = all_of![
+            request::method("POST"),
+            request::path(matches("/api/event/2024/quest/6/part/1/answer")),
+        ];
+        server.expect(Expectation::matching(m).respond_with(
+            status_code(200).body(r#"{"correct":true,"lengthCorrect":true,"firstCorrect":true,"time":1755169141515,"localTime":79507010,"globalTime":23803141515,"globalPlace":797,"globalScore":0}"#)
+        ));
+        let client = make_client(&server);
+        let start = Instant::now();
+        let response = client
+            .post_answer_when_allowed(
+                &PuzzleKey {
+                    event: 2024,
+                    quest: 6,
+                    part: Part::One,
+                },
+                "forty_two",
+            )
+            .unwrap();
+        assert!(response.correct);
+        assert!(start.elapsed() >= Duration::from_millis(200));
+        // A correct answer does not start a new penalty, so no need to ask again.
+        assert_eq!(None, client.get_penalty_delay().unwrap());
+    }
+
+    #[test]
+    fn test_wrong_answer_refreshes_penalty() {
+        let server = SERVER_POOL.get_server();
+        set_penalty_expect_times(&server, 0, 2);
+        let m = all_of![
+            request::method("POST"),
+            request::path(matches("/api/event/2024/quest/6/part/1/answer")),
+        ];
+        server.expect(Expectation::matching(m).respond_with(
+            status_code(200).body(r#"{"correct":false,"lengthCorrect":true,"firstCorrect":true,"time":1755169141515,"localTime":79507010,"globalTime":23803141515,"globalPlace":797,"globalScore":0}"#)
+        ));
+        let client = make_client(&server);
+        let key = PuzzleKey {
+            event: 2024,
+            quest: 6,
+            part: Part::One,
+        };
+        assert!(!client.post_answer_when_allowed(&key, "41").unwrap().correct);
+        client.get_penalty_delay().unwrap();
+        client.get_penalty_delay().unwrap();
+    }
+
     #[test]
     fn test_get_puzzle_input_key_not_available() {
         let server = SERVER_POOL.get_server();
@@ -3,6 +3,9 @@ use num::integer::lcm;
 use regex::Regex;
 use ring_algorithm::chinese_remainder_theorem;
 
+use crate::parse::Input;
+use crate::solver::SolveError;
+
 /// Returns disc-based coordinates: (disc_no, disc_position),
 /// where disc_no is 1-based, disc position is 0..disc_no.
 fn to_disc_coords((x, y): (i64, i64)) -> (i64, i64) {
@@ -13,16 +16,25 @@ fn to_flat_coords((disc_no, disc_position): (i64, i64)) -> (i64, i64) {
     (disc_position + 1, disc_no - disc_position)
 }
 
-pub fn solve_part_one(input: &str) -> String {
-    let re = Regex::new(r"x=(\d+) y=(\d+)").unwrap();
-    let mut snails = vec![];
-    for (_, [x_str, y_str]) in re.captures_iter(input).map(|c| c.extract()) {
-        snails.push(to_disc_coords((
-            x_str.parse().unwrap(),
-            y_str.parse().unwrap(),
-        )));
-    }
-    snails
+/// The snails' disc-based coordinates, one snail per line.
+fn parse_input(input: &str) -> Result<Vec<(i64, i64)>, SolveError> {
+    let re = Regex::new(r"^x=(\d+) y=(\d+)$").unwrap();
+    let input = Input::new(input);
+    input
+        .lines()
+        .map(|line| {
+            let captures = input.captures(&re, line)?;
+            Ok(to_disc_coords((
+                input.capture(&captures, 1)?,
+                input.capture(&captures, 2)?,
+            )))
+        })
+        .collect()
+}
+
+pub fn solve_part_one(input: &str) -> Result<String, SolveError> {
+    let snails = parse_input(input)?;
+    Ok(snails
         .into_iter()
         .map(|(disc_no, disc_position)| {
             let disc_position = (disc_position + 100) % disc_no;
@@ -31,18 +43,11 @@ pub fn solve_part_one(input: &str) -> String {
             x + (100 * y)
         })
         .sum::<i64>()
-        .to_string()
+        .to_string())
 }
 
-pub fn solve_part_two(input: &str) -> String {
-    let re = Regex::new(r"x=(\d+) y=(\d+)").unwrap();
-    let mut snails = vec![];
-    for (_, [x_str, y_str]) in re.captures_iter(input).map(|c| c.extract()) {
-        snails.push(to_disc_coords((
-            x_str.parse().unwrap(),
-            y_str.parse().unwrap(),
-        )));
-    }
+pub fn solve_part_two(input: &str) -> Result<String, SolveError> {
+    let snails = parse_input(input)?;
     let a: Vec<_> = snails
         .iter()
         .map(|(disc_no, disc_position)| disc_no - disc_position - 1)
@@ -51,10 +56,10 @@ pub fn solve_part_two(input: &str) -> String {
     debug!("{a:?} {m:?}");
     let n = chinese_remainder_theorem(a.as_slice(), m.as_slice()).unwrap();
     let lcm = m.into_iter().reduce(lcm).unwrap();
-    ((n + lcm) % lcm).to_string()
+    Ok(((n + lcm) % lcm).to_string())
 }
 
-pub fn solve_part_three(input: &str) -> String {
+pub fn solve_part_three(input: &str) -> Result<String, SolveError> {
     solve_part_two(input)
 }
 
@@ -73,6 +78,7 @@ x=2 y=3
 x=3 y=4
 x=4 y=4"
             )
+            .unwrap()
         );
     }
 
@@ -87,6 +93,7 @@ x=7 y=1
 x=1 y=5
 x=1 y=3"
             )
+            .unwrap()
         );
         assert_eq!(
             "13659",
@@ -97,6 +104,15 @@ x=1 y=5
 x=4 y=10
 x=5 y=3"
             )
+            .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_invalid_snail() {
+        assert_eq!(
+            "line 2, col 1: expected to match ^x=(\\d+) y=(\\d+)$, found \"x=2 y=\"",
+            solve_part_one("x=1 y=2\nx=2 y=").unwrap_err().to_string()
         );
     }
 }
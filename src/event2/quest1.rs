@@ -9,6 +9,9 @@ mod ffi {
     }
 }
 
+use crate::solver::Answer;
+use crate::solver::SolveError;
+
 pub fn solve_part_1(input: &str) -> String {
     ffi::solve_part_1(input)
 }
@@ -17,8 +20,19 @@ pub fn solve_part_2(input: &str) -> String {
     ffi::solve_part_2(input)
 }
 
-pub fn solve_part_3(input: &str) -> String {
-    ffi::solve_part_3(input)
+/// The C++ solver returns the minimum and maximum scores separated by a space.
+pub fn solve_part_3(input: &str) -> Result<Answer, SolveError> {
+    let scores = ffi::solve_part_3(input);
+    scores
+        .split_whitespace()
+        .map(|score| {
+            score
+                .parse::<i64>()
+                .map(Answer::Int)
+                .map_err(|_| SolveError::new(format!("unexpected score {score:?}")))
+        })
+        .collect::<Result<Vec<Answer>, SolveError>>()
+        .map(Answer::Multi)
 }
 
 #[cfg(test)]
@@ -122,6 +136,8 @@ LRLLLRRRLRLR
 LLRLLRLLLRRL
 LRLRLLLRRRRL"
             )
+            .unwrap()
+            .to_string()
         );
     }
 }
@@ -1,115 +1,69 @@
-use std::{
-    collections::HashSet,
-    ops::{Index, IndexMut},
-};
+use std::collections::HashSet;
 
-use array2d::Array2D;
 use itertools::Itertools;
 use log::debug;
 
-pub fn solve_part_1(input: &str) -> String {
-    let mut grid = Array2D::filled_with('.', 8, 8);
-    for (i, line) in input.lines().enumerate() {
-        for (j, ch) in line.chars().enumerate() {
-            grid[(i, j)] = ch;
-        }
-    }
+use crate::geometry::Point;
+use crate::grid::Grid;
+use crate::grid::GridViewMut;
+use crate::solver::SolveError;
+
+pub fn solve_part_1(input: &str) -> Result<String, SolveError> {
+    let mut grid = Grid::parse(input)?;
     for i in 2..6 {
         for j in 2..6 {
-            grid[(i, j)] = [0, 1, 6, 7]
+            grid[Point::new(j, i)] = [0, 1, 6, 7]
                 .iter()
                 .flat_map(|check_column| {
-                    let char_in_column = grid[(i, *check_column)];
+                    let char_in_column = grid[Point::new(*check_column, i)];
                     [0, 1, 6, 7]
                         .iter()
-                        .map(|check_row| grid[(*check_row, j)])
+                        .map(|check_row| grid[Point::new(j, *check_row)])
                         .filter(move |ch| *ch == char_in_column)
                 })
                 .exactly_one()
                 .unwrap();
         }
     }
-    String::from_iter((2..6).cartesian_product(2..6).map(|(j, i)| grid[(j, i)]))
-}
-
-struct ArrayView<'a, T> {
-    array: &'a mut Array2D<T>,
-    start_i: usize,
-    start_j: usize,
-    len_i: usize,
-    len_j: usize,
-}
-
-impl<'a, T> Index<(usize, usize)> for ArrayView<'a, T> {
-    type Output = T;
-
-    fn index(&self, (row, column): (usize, usize)) -> &Self::Output {
-        if row >= self.len_i || column >= self.len_j {
-            panic!(
-                "ArrayView[] with ({}, {}) exceeds len ({}, {})",
-                row, column, self.len_i, self.len_j
-            );
-        }
-        self.array
-            .get(row + self.start_i, column + self.start_j)
-            .expect("index out of bounds")
-    }
-}
-
-impl<'a, T> IndexMut<(usize, usize)> for ArrayView<'a, T> {
-    fn index_mut(&mut self, (row, column): (usize, usize)) -> &mut T {
-        if row >= self.len_i || column >= self.len_j {
-            panic!(
-                "ArrayView[] with ({}, {}) exceeds len ({}, {})",
-                row, column, self.len_i, self.len_j
-            );
-        }
-        self.array
-            .get_mut(row + self.start_i, column + self.start_j)
-            .expect("index out of bounds")
-    }
+    Ok(String::from_iter(
+        (2..6)
+            .cartesian_product(2..6)
+            .map(|(i, j)| grid[Point::new(j, i)]),
+    ))
 }
 
-fn find_runic_word(grid: &mut ArrayView<char>) -> String {
+fn find_runic_word(grid: &mut GridViewMut<char>) -> String {
     for i in 2..6 {
         for j in 2..6 {
-            grid[(i, j)] = [0, 1, 6, 7]
+            grid[Point::new(j, i)] = [0, 1, 6, 7]
                 .iter()
                 .flat_map(|check_column| {
-                    let char_in_column = grid[(i, *check_column)];
+                    let char_in_column = grid[Point::new(*check_column, i)];
                     [0, 1, 6, 7]
                         .iter()
-                        .map(|check_row| grid[(*check_row, j)])
+                        .map(|check_row| grid[Point::new(j, *check_row)])
                         .filter(move |ch| *ch == char_in_column)
                 })
                 .exactly_one()
                 .unwrap();
         }
     }
-    String::from_iter((2..6).cartesian_product(2..6).map(|(j, i)| grid[(j, i)]))
+    String::from_iter(
+        (2..6)
+            .cartesian_product(2..6)
+            .map(|(i, j)| grid[Point::new(j, i)]),
+    )
 }
 
-pub fn solve_part_2(input: &str) -> String {
-    let map_width = input.lines().next().unwrap().len();
-    let map_height = input.lines().count();
-    let mut grid = Array2D::filled_with('.', map_height, map_width);
-    for (i, line) in input.lines().enumerate() {
-        for (j, ch) in line.chars().enumerate() {
-            grid[(i, j)] = ch;
-        }
-    }
+pub fn solve_part_2(input: &str) -> Result<String, SolveError> {
+    let mut grid = Grid::parse(input)?;
+    let (map_height, map_width) = (grid.rows(), grid.columns());
     let mut start_i = 0;
     let mut start_j = 0;
     let mut total_power = 0;
     while start_i < map_height {
         while start_j < map_width {
-            let mut view = ArrayView {
-                array: &mut grid,
-                start_i,
-                start_j,
-                len_i: 8,
-                len_j: 8,
-            };
+            let mut view = grid.view_mut(Point::new(start_j, start_i), Point::new(8, 8));
             let word = find_runic_word(&mut view);
             let power: usize = word
                 .chars()
@@ -123,20 +77,20 @@ pub fn solve_part_2(input: &str) -> String {
         start_i += 9;
         start_j = 0;
     }
-    total_power.to_string()
+    Ok(total_power.to_string())
 }
 
-fn solve_runic_section(grid: &mut ArrayView<char>) -> Option<String> {
+fn solve_runic_section(grid: &mut GridViewMut<char>) -> Option<String> {
     let mut pending_coordinates: Vec<(usize, usize)> = vec![];
     for i in 2..6 {
         for j in 2..6 {
             let row_letters: Vec<char> = [0, 1, 6, 7]
                 .iter()
-                .map(|border_j| grid[(i, *border_j)])
+                .map(|border_j| grid[Point::new(*border_j, i)])
                 .collect();
             let col_letters: Vec<char> = [0, 1, 6, 7]
                 .iter()
-                .map(|border_i| grid[(*border_i, j)])
+                .map(|border_i| grid[Point::new(j, *border_i)])
                 .collect();
             let mut solution = ' ';
             for r in &row_letters {
@@ -153,7 +107,7 @@ fn solve_runic_section(grid: &mut ArrayView<char>) -> Option<String> {
                     pending_coordinates.push((i, j));
                 }
             } else {
-                grid[(i, j)] = solution;
+                grid[Point::new(j, i)] = solution;
             }
         }
     }
@@ -163,11 +117,11 @@ fn solve_runic_section(grid: &mut ArrayView<char>) -> Option<String> {
         for (i, j) in pending_coordinates {
             let row_letters: Vec<(usize, char)> = [0, 1, 6, 7]
                 .iter()
-                .map(|border_j| (*border_j, grid[(i, *border_j)]))
+                .map(|border_j| (*border_j, grid[Point::new(*border_j, i)]))
                 .collect();
             let col_letters: Vec<(usize, char)> = [0, 1, 6, 7]
                 .iter()
-                .map(|border_i| (*border_i, grid[(*border_i, j)]))
+                .map(|border_i| (*border_i, grid[Point::new(j, *border_i)]))
                 .collect();
             if let Some(question_idx) = row_letters.iter().find(|ch| ch.1 == '?') {
                 // Question mark is in the row
@@ -184,13 +138,13 @@ fn solve_runic_section(grid: &mut ArrayView<char>) -> Option<String> {
                 let solutions: Vec<_> = col_letters
                     .iter()
                     .map(|p| p.1)
-                    .filter(|ch| !(2..6).any(|i| grid[(i, j)] == *ch))
+                    .filter(|ch| !(2..6).any(|i| grid[Point::new(j, i)] == *ch))
                     .collect();
                 if solutions.len() > 1 {
                     new_pending_coordinates.push((i, j));
                 } else {
-                    grid[(i, j)] = solutions[0];
-                    grid[(i, question_idx.0)] = solutions[0];
+                    grid[Point::new(j, i)] = solutions[0];
+                    grid[Point::new(question_idx.0, i)] = solutions[0];
                     continue_solving = true;
                 }
                 continue;
@@ -199,13 +153,13 @@ fn solve_runic_section(grid: &mut ArrayView<char>) -> Option<String> {
                 let solutions: Vec<_> = row_letters
                     .iter()
                     .map(|p| p.1)
-                    .filter(|ch| !(2..6).any(|j| grid[(i, j)] == *ch))
+                    .filter(|ch| !(2..6).any(|j| grid[Point::new(j, i)] == *ch))
                     .collect();
                 if solutions.len() > 1 {
                     new_pending_coordinates.push((i, j));
                 } else {
-                    grid[(i, j)] = solutions[0];
-                    grid[(question_idx.0, j)] = solutions[0];
+                    grid[Point::new(j, i)] = solutions[0];
+                    grid[Point::new(j, question_idx.0)] = solutions[0];
                     continue_solving = true;
                 }
                 continue;
@@ -221,20 +175,16 @@ fn solve_runic_section(grid: &mut ArrayView<char>) -> Option<String> {
         None
     } else {
         Some(String::from_iter(
-            (2..6).cartesian_product(2..6).map(|(j, i)| grid[(j, i)]),
+            (2..6)
+                .cartesian_product(2..6)
+                .map(|(i, j)| grid[Point::new(j, i)]),
         ))
     }
 }
 
-pub fn solve_part_3(input: &str) -> String {
-    let map_width = input.lines().next().unwrap().len();
-    let map_height = input.lines().count();
-    let mut grid = Array2D::filled_with('.', map_height, map_width);
-    for (i, line) in input.lines().enumerate() {
-        for (j, ch) in line.chars().enumerate() {
-            grid[(i, j)] = ch;
-        }
-    }
+pub fn solve_part_3(input: &str) -> Result<String, SolveError> {
+    let mut grid = Grid::parse(input)?;
+    let (map_height, map_width) = (grid.rows(), grid.columns());
     let mut total_power = 0;
     let mut solved = HashSet::new();
     let mut continue_solving = false;
@@ -247,13 +197,7 @@ pub fn solve_part_3(input: &str) -> String {
                     start_j += 6;
                     continue;
                 }
-                let mut view = ArrayView {
-                    array: &mut grid,
-                    start_i,
-                    start_j,
-                    len_i: 8,
-                    len_j: 8,
-                };
+                let mut view = grid.view_mut(Point::new(start_j, start_i), Point::new(8, 8));
                 if let Some(word) = solve_runic_section(&mut view) {
                     let power: usize = word
                         .chars()
@@ -275,7 +219,7 @@ pub fn solve_part_3(input: &str) -> String {
         }
         continue_solving = false;
     }
-    total_power.to_string()
+    Ok(total_power.to_string())
 }
 
 #[cfg(test)]
@@ -298,6 +242,7 @@ SG....MN
 **FTZV**
 **GMJH**"
             )
+            .unwrap()
         );
     }
 
@@ -315,6 +260,7 @@ SG....MN
 **FTZV**
 **GMJH**"
             )
+            .unwrap()
         );
     }
 
@@ -338,6 +284,7 @@ TJ....YK....LP
 **XRTK**BMSP**
 **DWZN**GCJV**"
             )
+            .unwrap()
         );
     }
 }
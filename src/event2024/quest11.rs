@@ -1,5 +1,14 @@
 use std::collections::HashMap;
 
+use crate::parse::Input;
+use crate::solver::SolveError;
+
+/// The termite generations: each id with the ids it turns into the next day.
+fn parse_input(input: &str) -> Result<Vec<(&str, Vec<&str>)>, SolveError> {
+    let input = Input::new(input);
+    input.lines().map(|line| input.adjacency(line)).collect()
+}
+
 fn count_generations(data: &Vec<(&str, Vec<&str>)>, id: &str, gens: u8) -> u64 {
     let mut pops = HashMap::new();
     pops.insert(id, 1);
@@ -15,48 +24,24 @@ fn count_generations(data: &Vec<(&str, Vec<&str>)>, id: &str, gens: u8) -> u64 {
     pops.into_values().sum()
 }
 
-pub fn solve_part_1(input: &str) -> String {
-    let data: Vec<(&str, Vec<&str>)> = input
-        .lines()
-        .map(|line| {
-            let mut split = line.split(":");
-            let id = split.next().unwrap();
-            let children = split.next().unwrap().split(',').collect();
-            (id, children)
-        })
-        .collect();
-    count_generations(&data, "A", 4).to_string()
+pub fn solve_part_1(input: &str) -> Result<String, SolveError> {
+    let data = parse_input(input)?;
+    Ok(count_generations(&data, "A", 4).to_string())
 }
 
-pub fn solve_part_2(input: &str) -> String {
-    let data: Vec<(&str, Vec<&str>)> = input
-        .lines()
-        .map(|line| {
-            let mut split = line.split(":");
-            let id = split.next().unwrap();
-            let children = split.next().unwrap().split(',').collect();
-            (id, children)
-        })
-        .collect();
-    count_generations(&data, "Z", 10).to_string()
+pub fn solve_part_2(input: &str) -> Result<String, SolveError> {
+    let data = parse_input(input)?;
+    Ok(count_generations(&data, "Z", 10).to_string())
 }
 
-pub fn solve_part_3(input: &str) -> String {
-    let data: Vec<(&str, Vec<&str>)> = input
-        .lines()
-        .map(|line| {
-            let mut split = line.split(":");
-            let id = split.next().unwrap();
-            let children = split.next().unwrap().split(',').collect();
-            (id, children)
-        })
-        .collect();
+pub fn solve_part_3(input: &str) -> Result<String, SolveError> {
+    let data = parse_input(input)?;
     let ids: Vec<&str> = data.iter().map(|entry| entry.0).collect();
     let pops: Vec<u64> = ids
         .iter()
         .map(|id| count_generations(&data, id, 20))
         .collect();
-    (pops.iter().max().unwrap() - pops.iter().min().unwrap()).to_string()
+    Ok((pops.iter().max().unwrap() - pops.iter().min().unwrap()).to_string())
 }
 
 #[cfg(test)]
@@ -74,6 +59,7 @@ mod tests {
 B:C,A
 C:A"
             )
+            .unwrap()
         );
     }
 
@@ -86,6 +72,7 @@ C:A"
 B:C,Z
 C:Z"
             )
+            .unwrap()
         );
     }
 
@@ -98,6 +85,7 @@ C:Z"
 B:C,A,A
 C:A"
             )
+            .unwrap()
         );
     }
 }
@@ -1,43 +1,45 @@
 use std::collections::{HashMap, HashSet};
 
+use crate::geometry::Dir4;
+use crate::geometry::Point;
+use crate::trace;
+
 fn solve_maze(
-    platform_initial_offsets: &HashMap<(usize, usize), u8>,
-    start_positions: &[(usize, usize)],
-    end_position: (usize, usize),
+    platform_initial_offsets: &HashMap<Point<usize>, u8>,
+    start_positions: &[Point<usize>],
+    end_position: Point<usize>,
 ) -> usize {
     let mut state_front = HashSet::new();
-    for (i, j) in start_positions {
-        state_front.insert((*i, *j, 0));
+    for &position in start_positions {
+        state_front.insert((position, 0));
     }
     let mut t = 0;
     let mut visited = HashSet::new();
     loop {
+        trace::count("front", t as u64, state_front.len());
         let mut new_state_front = HashSet::new();
-        for (i, j, z) in state_front {
-            if (i, j) == end_position {
+        for (position, z) in state_front {
+            if position == end_position {
                 return t;
             }
-            visited.insert((i, j));
-            for (di, dj) in [(-1, 0), (0, -1), (1, 0), (0, 1)] {
-                if i == 0 && di < 0 {
-                    continue;
-                }
-                if j == 0 && dj < 0 {
+            if visited.insert(position) {
+                trace::moved("reached", t as u64, (position.x as i64, position.y as i64));
+            }
+            for dir in Dir4::ALL {
+                let Some(next) = position.checked_step(dir) else {
                     continue;
-                }
-                let ni = (i as isize + di) as usize;
-                let nj = (j as isize + dj) as usize;
-                if visited.contains(&(ni, nj)) {
+                };
+                if visited.contains(&next) {
                     continue;
                 }
-                if let Some(platform_initial_z) = platform_initial_offsets.get(&(ni, nj))
+                if let Some(platform_initial_z) = platform_initial_offsets.get(&next)
                     && *platform_initial_z == z
                 {
-                    new_state_front.insert((ni, nj, z));
+                    new_state_front.insert((next, z));
                 }
             }
-            new_state_front.insert((i, j, (z + 1) % 10));
-            new_state_front.insert((i, j, (z + 9) % 10));
+            new_state_front.insert((position, (z + 1) % 10));
+            new_state_front.insert((position, (z + 9) % 10));
         }
         state_front = new_state_front;
         t += 1;
@@ -45,24 +47,25 @@ fn solve_maze(
 }
 
 pub fn solve_part_1(input: &str) -> String {
-    let mut platform_initial_offsets: HashMap<(usize, usize), u8> = HashMap::new();
+    let mut platform_initial_offsets: HashMap<Point<usize>, u8> = HashMap::new();
     let mut start_position = None;
     let mut end_position = None;
     for (i, line) in input.lines().enumerate() {
         for (j, ch) in line.chars().enumerate() {
+            let position = Point::new(j, i);
             match ch {
                 'S' => {
-                    start_position = Some((i, j));
-                    platform_initial_offsets.insert((i, j), 0);
+                    start_position = Some(position);
+                    platform_initial_offsets.insert(position, 0);
                 }
                 'E' => {
-                    end_position = Some((i, j));
-                    platform_initial_offsets.insert((i, j), 0);
+                    end_position = Some(position);
+                    platform_initial_offsets.insert(position, 0);
                 }
                 '#' => {}
                 ' ' => {}
                 ch => {
-                    platform_initial_offsets.insert((i, j), ch as u8 - b'0');
+                    platform_initial_offsets.insert(position, ch as u8 - b'0');
                 }
             }
         }
@@ -77,23 +80,24 @@ pub fn solve_part_2(input: &str) -> String {
 }
 
 pub fn solve_part_3(input: &str) -> String {
-    let mut platform_initial_offsets: HashMap<(usize, usize), u8> = HashMap::new();
+    let mut platform_initial_offsets: HashMap<Point<usize>, u8> = HashMap::new();
     let mut start_positions = vec![];
     let mut end_position = None;
     for (i, line) in input.lines().enumerate() {
         for (j, ch) in line.chars().enumerate() {
+            let position = Point::new(j, i);
             match ch {
                 'S' => {
-                    start_positions.push((i, j));
+                    start_positions.push(position);
                 }
                 'E' => {
-                    end_position = Some((i, j));
-                    platform_initial_offsets.insert((i, j), 0);
+                    end_position = Some(position);
+                    platform_initial_offsets.insert(position, 0);
                 }
                 '#' => {}
                 ' ' => {}
                 ch => {
-                    platform_initial_offsets.insert((i, j), ch as u8 - b'0');
+                    platform_initial_offsets.insert(position, ch as u8 - b'0');
                 }
             }
         }
@@ -1,107 +1,76 @@
-use std::{
-    cmp::max,
-    collections::{HashMap, HashSet},
-};
+use std::collections::HashSet;
 
-use petgraph::{algo::floyd_warshall, graph::UnGraph};
+use crate::parse::Input;
+use crate::solver::SolveError;
+use crate::space::Dir6;
+use crate::space::Point3;
+use crate::space::distances;
+use crate::space::trace;
 
-pub fn solve_part_1(input: &str) -> String {
-    let instructions: Vec<(char, i32)> = input
-        .split(",")
-        .map(|cmd| (cmd.chars().next().unwrap(), cmd[1..].parse().unwrap()))
-        .collect();
-    let mut height = 0;
-    let mut z = 0;
-    for (instruction, param) in instructions {
-        match instruction {
-            'U' => {
-                z += param;
-                height = max(height, z);
-            }
-            'D' => {
-                z -= param;
-            }
-            _ => {}
-        }
-    }
-    height.to_string()
+type Segment = Point3<isize>;
+
+/// The segments a branch grows through, from the ground up.
+fn grow(input: &Input, line: &str) -> Result<Vec<Segment>, SolveError> {
+    let moves = input.instructions_with(line, |token, letter| {
+        Dir6::from_letter(letter).ok_or_else(|| {
+            input.error(
+                token,
+                format!("expected one of U, D, R, L, F, B, found direction {letter:?}"),
+            )
+        })
+    })?;
+    Ok(trace(Point3::default(), moves))
 }
 
-type Coords = (isize, isize, isize);
+pub fn solve_part_1(input: &str) -> Result<String, SolveError> {
+    let input = Input::new(input);
+    let branch = grow(&input, input.lines().next().unwrap_or_default())?;
+    let height = branch.iter().map(|segment| segment.z).max().unwrap_or(0);
+    Ok(height.max(0).to_string())
+}
 
-fn make_tree(input: &str) -> (HashSet<Coords>, HashSet<Coords>) {
-    let mut segments = HashSet::<Coords>::new();
+/// The segments of the tree, and its leaves.
+fn make_tree(input: &str) -> Result<(HashSet<Segment>, HashSet<Segment>), SolveError> {
+    let input = Input::new(input);
+    let mut segments = HashSet::new();
     let mut leaves = HashSet::new();
     for line in input.lines() {
-        let instructions: Vec<(char, i32)> = line
-            .split(",")
-            .map(|cmd| (cmd.chars().next().unwrap(), cmd[1..].parse().unwrap()))
-            .collect();
-        let mut x = 0;
-        let mut y = 0;
-        let mut z = 0;
-        for (instruction, param) in instructions {
-            let dir = match instruction {
-                'U' => (0, 0, 1),
-                'D' => (0, 0, -1),
-                'R' => (1, 0, 0),
-                'L' => (-1, 0, 0),
-                'F' => (0, 1, 0),
-                'B' => (0, -1, 0),
-                _ => unreachable!(),
-            };
-            for _ in 0..param {
-                x += dir.0;
-                y += dir.1;
-                z += dir.2;
-                segments.insert((x, y, z));
-            }
-        }
-        leaves.insert((x, y, z));
+        let branch = grow(&input, line)?;
+        leaves.insert(branch.last().copied().unwrap_or_default());
+        segments.extend(branch);
     }
-    (segments, leaves)
+    Ok((segments, leaves))
 }
 
-pub fn solve_part_2(input: &str) -> String {
-    make_tree(input).0.len().to_string()
+pub fn solve_part_2(input: &str) -> Result<String, SolveError> {
+    Ok(make_tree(input)?.0.len().to_string())
 }
 
-pub fn solve_part_3(input: &str) -> String {
-    let (tree, leaves) = make_tree(input);
-    let mut segment_to_node_id = HashMap::<_, _>::new();
-    let mut graph = UnGraph::new_undirected();
-    for segment in tree.iter() {
-        segment_to_node_id.insert(segment, graph.add_node(()));
-    }
-    for (x, y, z) in tree.iter() {
-        let from_node = segment_to_node_id[&(*x, *y, *z)];
-        for (dx, dy, dz) in [
-            (0, 0, 1),
-            (0, 0, -1),
-            (1, 0, 0),
-            (-1, 0, 0),
-            (0, 1, 0),
-            (0, -1, 0),
-        ] {
-            if let Some(to_node) = segment_to_node_id.get(&(x + dx, y + dy, z + dz)) {
-                graph.add_edge(from_node, *to_node, ());
-            }
+pub fn solve_part_3(input: &str) -> Result<String, SolveError> {
+    let (tree, leaves) = make_tree(input)?;
+    let trunk: Vec<_> = tree
+        .iter()
+        .filter(|segment| segment.x == 0 && segment.y == 0)
+        .collect();
+    // There are few leaves, so a search from each of them is much cheaper than
+    // finding the distances between all pairs of segments.
+    let mut murkiness = vec![0; trunk.len()];
+    for leaf in leaves {
+        let distances = distances([leaf], &tree);
+        for (sum, segment) in murkiness.iter_mut().zip(&trunk) {
+            *sum += distances.get(*segment).ok_or_else(|| {
+                SolveError::new(format!(
+                    "the trunk at height {} cannot be reached from the leaf at {leaf:?}",
+                    segment.z
+                ))
+            })?;
         }
     }
-    let all_paths_shortest_paths = floyd_warshall(&graph, |_| 1).unwrap();
-    segment_to_node_id
-        .iter()
-        .filter(|((x, y, _), _)| *x == 0 && *y == 0)
-        .map(|((_, _, _), trunk_node_id)| {
-            leaves
-                .iter()
-                .map(|leaf| segment_to_node_id[leaf])
-                .map(|leaf_node_id| all_paths_shortest_paths[&(*trunk_node_id, leaf_node_id)])
-                .sum::<isize>()
-        })
+    murkiness
+        .into_iter()
         .min()
-        .unwrap()
-        .to_string()
+        .map(|murkiness| murkiness.to_string())
+        .ok_or_else(|| SolveError::new("the tree has no trunk"))
 }
 
 #[cfg(test)]
@@ -111,19 +80,37 @@ mod tests {
 
     #[test]
     fn test_part_1() {
-        assert_eq!("7", solve_part_1("U5,R3,D2,L5,U4,R5,D2"));
+        assert_eq!("7", solve_part_1("U5,R3,D2,L5,U4,R5,D2").unwrap());
     }
 
     #[test]
     fn test_part_2() {
-        assert_eq!("24", solve_part_2("U5,R3,D2,L5,U4,R5,D2"));
-        assert_eq!("14", solve_part_2("U6,L1,D2,R3,U2,L1"));
+        assert_eq!("24", solve_part_2("U5,R3,D2,L5,U4,R5,D2").unwrap());
+        assert_eq!("14", solve_part_2("U6,L1,D2,R3,U2,L1").unwrap());
         assert_eq!(
             "32",
             solve_part_2(
                 "U5,R3,D2,L5,U4,R5,D2
 U6,L1,D2,R3,U2,L1"
             )
+            .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_invalid_direction() {
+        assert_eq!(
+            "line 1, col 4: expected one of U, D, R, L, F, B, found direction 'N'",
+            solve_part_2("U5,N3").unwrap_err().to_string()
+        );
+    }
+
+    #[test]
+    fn test_disconnected_branch() {
+        assert_eq!(
+            "the trunk at height 1 cannot be reached from the leaf at \
+             Point3 { x: 2, y: 0, z: 0 }",
+            solve_part_3("U1\nR2").unwrap_err().to_string()
         );
     }
 
@@ -135,6 +122,7 @@ U6,L1,D2,R3,U2,L1"
                 "U5,R3,D2,L5,U4,R5,D2
 U6,L1,D2,R3,U2,L1"
             )
+            .unwrap()
         );
         assert_eq!(
             "46",
@@ -145,6 +133,7 @@ U30,L2,F1,R1,B1,R1,F2,U1,F1
 U25,R1,L2,B1,U1,R2,F1,L2
 U16,L1,B1,L1,B3,L1,B1,F1"
             )
+            .unwrap()
         );
     }
 }
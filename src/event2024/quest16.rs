@@ -3,6 +3,10 @@ use std::collections::HashMap;
 
 use itertools::Itertools;
 
+use crate::solver::Param;
+use crate::solver::Params;
+use crate::solver::SolveError;
+
 fn parse_machine(input: &str) -> (Vec<usize>, Vec<Vec<&str>>) {
     let shifts: Vec<usize> = input
         .lines()
@@ -83,8 +87,11 @@ fn solve_part_2_with_count(input: &str, count: usize) -> String {
     (total_winnings * cycle_count + winnings_after_pull[remainder]).to_string()
 }
 
-pub fn solve_part_2(input: &str) -> String {
-    solve_part_2_with_count(input, 202420242024)
+/// How many times the lever is pulled.
+pub const COUNT: Param<usize> = Param::new("count", "202420242024");
+
+pub fn solve_part_2(input: &str, params: &Params) -> Result<String, SolveError> {
+    Ok(solve_part_2_with_count(input, params.get(&COUNT)?))
 }
 
 pub fn solve_part_3(input: &str) -> String {
@@ -215,7 +222,22 @@ mod tests {
 >.- ^_^ >.<
 -_- -.- >.<
     -.^ ^_^
-    >.>"
+    >.>",
+                &Params::default()
+            )
+            .unwrap()
+        );
+        assert_eq!(
+            Ok(String::from("15")),
+            solve_part_2(
+                "1,2,3
+
+^_^ -.- ^,-
+>.- ^_^ >.<
+-_- -.- >.<
+    -.^ ^_^
+    >.>",
+                &Params::from_iter([("count", "10")])
             )
         );
     }
@@ -7,16 +7,14 @@ use petgraph::{
     visit::{EdgeRef, IntoEdgeReferences, IntoNodeIdentifiers, depth_first_search},
 };
 
-fn manhattan(from: (usize, usize), to: (usize, usize)) -> usize {
-    from.0.abs_diff(to.0) + from.1.abs_diff(to.1)
-}
+use crate::geometry::Point;
 
 pub fn solve_part_1(input: &str) -> String {
     let mut graph: UnGraphMap<_, _> = UnGraphMap::new();
     for (y, line) in input.lines().enumerate() {
         for (x, ch) in line.chars().enumerate() {
             if ch == '*' {
-                graph.add_node((x, y));
+                graph.add_node(Point::new(x, y));
             }
         }
     }
@@ -24,7 +22,7 @@ pub fn solve_part_1(input: &str) -> String {
     for from_node in all_nodes.iter() {
         for to_node in all_nodes.iter() {
             if *to_node != *from_node {
-                graph.add_edge(*from_node, *to_node, manhattan(*from_node, *to_node));
+                graph.add_edge(*from_node, *to_node, from_node.manhattan(*to_node));
             }
         }
     }
@@ -34,7 +32,7 @@ pub fn solve_part_1(input: &str) -> String {
         .map(|edge_ref| {
             let from = constellation.node_weight(edge_ref.source()).unwrap();
             let to = constellation.node_weight(edge_ref.target()).unwrap();
-            manhattan(*from, *to)
+            from.manhattan(*to)
         })
         .sum::<usize>()
         + constellation.node_identifiers().count())
@@ -50,7 +48,7 @@ pub fn solve_part_3(input: &str) -> String {
     for (y, line) in input.lines().enumerate() {
         for (x, ch) in line.chars().enumerate() {
             if ch == '*' {
-                graph.add_node((x, y));
+                graph.add_node(Point::new(x, y));
             }
         }
     }
@@ -58,9 +56,9 @@ pub fn solve_part_3(input: &str) -> String {
     for from_node in all_nodes.iter() {
         for to_node in all_nodes.iter() {
             if *to_node != *from_node {
-                let distance = manhattan(*from_node, *to_node);
+                let distance = from_node.manhattan(*to_node);
                 if distance < 6 {
-                    graph.add_edge(*from_node, *to_node, manhattan(*from_node, *to_node));
+                    graph.add_edge(*from_node, *to_node, from_node.manhattan(*to_node));
                 }
             }
         }
@@ -80,7 +78,7 @@ pub fn solve_part_3(input: &str) -> String {
                     node_count += 1;
                 }
                 petgraph::visit::DfsEvent::TreeEdge(from_node, to_node) => {
-                    distances_sum += manhattan(from_node, to_node);
+                    distances_sum += from_node.manhattan(to_node);
                 }
                 _ => {}
             },
@@ -1,199 +1,90 @@
-use array2d::Array2D;
-use log::debug;
 use permutations::Permutation;
 
-const ROTATABLE_OFFSETS: [(isize, isize); 8] = [
-    (-1, -1),
-    (0, -1),
-    (1, -1),
-    (1, 0),
-    (1, 1),
-    (0, 1),
-    (-1, 1),
-    (-1, 0),
-];
+use crate::geometry::Point;
+use crate::grid::Grid;
+use crate::parse::Input;
+use crate::solver::SolveError;
 
-fn rotate(grid: &mut Array2D<char>, x: usize, y: usize, direction: char) {
-    let string: Vec<_> = ROTATABLE_OFFSETS
-        .iter()
-        .map(|(dx, dy)| grid[(x.wrapping_add_signed(*dx), y.wrapping_add_signed(*dy))])
-        .collect();
-    for (idx, (dx, dy)) in ROTATABLE_OFFSETS.into_iter().enumerate() {
-        match direction {
-            'R' => {
-                grid[(x.wrapping_add_signed(dx), y.wrapping_add_signed(dy))] =
-                    string[(idx + string.len() - 1) % string.len()]
-            }
-            'L' => {
-                grid[(x.wrapping_add_signed(dx), y.wrapping_add_signed(dy))] =
-                    string[(idx + 1) % string.len()]
-            }
-            _ => unreachable!(),
-        }
+/// The rotation key and the grid holding the message.
+fn parse_input(input: &str) -> Result<(Vec<char>, Grid<char>), SolveError> {
+    let input = Input::new(input);
+    let (key, grid) = input.two_sections()?;
+    if let Some((i, c)) = key.char_indices().find(|(_, c)| !matches!(c, 'L' | 'R')) {
+        return Err(input.error(&key[i..], format!("expected L or R, found {c:?}")));
     }
+    Ok((key.chars().collect(), Grid::parse(grid)?))
 }
 
-fn to_row_major(x: usize, y: usize, width: usize) -> usize {
-    y * width + x
+/// Maps every cell, in row-major order, to the cell its character comes from once the
+/// ring of cells around `centre` is rotated clockwise (`R`) or counterclockwise.
+fn rotation(grid: &Grid<char>, centre: Point<usize>, direction: char) -> Permutation {
+    let index = |point: Point<usize>| point.y * grid.columns() + point.x;
+    // Clockwise, starting from the cell above.
+    let ring: Vec<Point<usize>> = grid.neighbours_8(centre).collect();
+    let mut sources: Vec<usize> = (0..grid.rows() * grid.columns()).collect();
+    for (k, &position) in ring.iter().enumerate() {
+        let source = match direction {
+            'R' => ring[(k + ring.len() - 1) % ring.len()],
+            _ => ring[(k + 1) % ring.len()],
+        };
+        sources[index(position)] = index(source);
+    }
+    Permutation::try_from(sources).unwrap()
 }
 
-fn from_row_major(position: usize, width: usize) -> (usize, usize) {
-    let x = position % width;
-    let y = position / width;
-    (x, y)
+/// Rotates around every inner cell in turn, cycling through the key, and repeats that
+/// the given number of times.
+fn decode(grid: &Grid<char>, key: &[char], rounds: u32) -> Grid<char> {
+    let columns = grid.columns();
+    let centres =
+        (1..grid.rows() - 1).flat_map(|y| (1..columns - 1).map(move |x| Point::new(x, y)));
+    let permutation = centres
+        .zip(key.iter().cycle())
+        .fold(
+            Permutation::identity(grid.rows() * columns),
+            |p, (centre, &direction)| rotation(grid, centre, direction) * p,
+        )
+        .pow(rounds);
+    let rows = (0..grid.rows())
+        .map(|i| {
+            (0..columns)
+                .map(|j| {
+                    let n = permutation.apply(i * columns + j);
+                    grid[Point::new(n % columns, n / columns)]
+                })
+                .collect()
+        })
+        .collect();
+    Grid::from_rows(rows).unwrap()
 }
 
-fn make_rotate_permutation(
-    x: usize,
-    y: usize,
-    width: usize,
-    height: usize,
-    direction: char,
-) -> Permutation {
-    let coords_to_rotate: Vec<_> = ROTATABLE_OFFSETS
-        .iter()
-        .map(|(dx, dy)| {
-            to_row_major(
-                x.wrapping_add_signed(*dx),
-                y.wrapping_add_signed(*dy),
-                width,
-            )
+/// The message between `>` and `<` in a row of the grid.
+fn message(grid: &Grid<char>) -> Result<String, SolveError> {
+    (0..grid.rows())
+        .find_map(|i| {
+            let row: String = grid.row(i).iter().collect();
+            let (_, rest) = row.split_once('>')?;
+            let (message, _) = rest.split_once('<')?;
+            Some(message.to_string())
         })
-        .collect();
-    let mut index_vector: Vec<_> = (0..(width * height)).collect();
-    for (idx, (dx, dy)) in ROTATABLE_OFFSETS.into_iter().enumerate() {
-        let nx = x.wrapping_add_signed(dx);
-        let ny = y.wrapping_add_signed(dy);
-        match direction {
-            'R' => {
-                index_vector[to_row_major(nx, ny, width)] =
-                    coords_to_rotate[(idx + coords_to_rotate.len() - 1) % coords_to_rotate.len()]
-            }
-            'L' => {
-                index_vector[to_row_major(nx, ny, width)] =
-                    coords_to_rotate[(idx + 1) % coords_to_rotate.len()]
-            }
-            _ => unreachable!(),
-        }
-    }
-    Permutation::try_from(index_vector).unwrap()
+        .ok_or_else(|| SolveError::new("no message between > and < found"))
 }
 
-pub fn solve_part_1(input: &str) -> String {
-    let width = input.lines().nth(2).unwrap().chars().count();
-    let height = input.lines().count() - 2;
-    let mut grid = Array2D::<char>::filled_with(' ', width, height);
-    for (y, line) in input.lines().skip(2).enumerate() {
-        for (x, ch) in line.chars().enumerate() {
-            grid[(x, y)] = ch;
-        }
-    }
-    let key: Vec<_> = input.lines().next().unwrap().chars().collect();
-    let mut key_pos = 0;
-    for y in 1..height - 1 {
-        for x in 1..width - 1 {
-            debug!("{grid:?}");
-            rotate(&mut grid, x, y, key[key_pos]);
-            key_pos = (key_pos + 1) % key.len();
-        }
-    }
-    let mut result = String::new();
-    for y in 0..height {
-        let mut is_in_string = false;
-        for x in 0..width {
-            if is_in_string {
-                if grid[(x, y)] == '<' {
-                    return result;
-                }
-                result.push(grid[(x, y)]);
-                continue;
-            }
-            if grid[(x, y)] == '>' {
-                is_in_string = true;
-            }
-        }
-    }
-    unreachable!()
+fn solve(input: &str, rounds: u32) -> Result<String, SolveError> {
+    let (key, grid) = parse_input(input)?;
+    message(&decode(&grid, &key, rounds))
 }
 
-pub fn solve_part_2(input: &str) -> String {
-    let width = input.lines().nth(2).unwrap().chars().count();
-    let height = input.lines().count() - 2;
-    let mut grid = Array2D::<char>::filled_with(' ', width, height);
-    for (y, line) in input.lines().skip(2).enumerate() {
-        for (x, ch) in line.chars().enumerate() {
-            grid[(x, y)] = ch;
-        }
-    }
-    let key: Vec<_> = input.lines().next().unwrap().chars().collect();
-    let mut key_pos = 0;
-    let mut permutation = Permutation::identity(width * height);
-    for y in 1..height - 1 {
-        for x in 1..width - 1 {
-            permutation = make_rotate_permutation(x, y, width, height, key[key_pos]) * permutation;
-            key_pos = (key_pos + 1) % key.len();
-        }
-    }
-    permutation = permutation.pow(100);
-    let mut result = String::new();
-    for y in 0..height {
-        let mut is_in_string = false;
-        for x in 0..width {
-            let coords = from_row_major(permutation.apply(to_row_major(x, y, width)), width);
-            let (x, y) = coords;
-            if is_in_string {
-                if grid[(x, y)] == '<' {
-                    return result;
-                }
-                result.push(grid[(x, y)]);
-                continue;
-            }
-            if grid[(x, y)] == '>' {
-                is_in_string = true;
-            }
-        }
-    }
-    unreachable!()
+pub fn solve_part_1(input: &str) -> Result<String, SolveError> {
+    solve(input, 1)
 }
 
-pub fn solve_part_3(input: &str) -> String {
-    let width = input.lines().nth(2).unwrap().chars().count();
-    let height = input.lines().count() - 2;
-    let mut grid = Array2D::<char>::filled_with(' ', width, height);
-    for (y, line) in input.lines().skip(2).enumerate() {
-        for (x, ch) in line.chars().enumerate() {
-            grid[(x, y)] = ch;
-        }
-    }
-    let key: Vec<_> = input.lines().next().unwrap().chars().collect();
-    let mut key_pos = 0;
-    let mut permutation = Permutation::identity(width * height);
-    for y in 1..height - 1 {
-        for x in 1..width - 1 {
-            permutation = make_rotate_permutation(x, y, width, height, key[key_pos]) * permutation;
-            key_pos = (key_pos + 1) % key.len();
-        }
-    }
-    permutation = permutation.pow(1048576000);
-    let mut result = String::new();
-    for y in 0..height {
-        let mut is_in_string = false;
-        for x in 0..width {
-            let coords = from_row_major(permutation.apply(to_row_major(x, y, width)), width);
-            let (x, y) = coords;
-            if is_in_string {
-                if grid[(x, y)] == '<' {
-                    return result;
-                }
-                result.push(grid[(x, y)]);
-                continue;
-            }
-            if grid[(x, y)] == '>' {
-                is_in_string = true;
-            }
-        }
-    }
-    unreachable!()
+pub fn solve_part_2(input: &str) -> Result<String, SolveError> {
+    solve(input, 100)
+}
+
+pub fn solve_part_3(input: &str) -> Result<String, SolveError> {
+    solve(input, 1048576000)
 }
 
 #[cfg(test)]
@@ -212,6 +103,7 @@ mod tests {
 -----
 W---<"
             )
+            .unwrap()
         );
     }
 
@@ -229,47 +121,23 @@ A.VI..>...T
 .....F..G..
 D.H........"
             )
+            .unwrap()
         );
     }
 
     #[test]
-    fn test_to_row_major() {
-        assert_eq!(17, to_row_major(2, 5, 3));
-    }
-
-    #[test]
-    fn test_from_row_major() {
-        assert_eq!((2, 5), from_row_major(17, 3));
-    }
-
-    #[test]
-    fn test_make_rotate_permutation_right() {
-        // A B C . .         D A B . .
-        // D E F . .    =>   G E C . .
-        // G H I . .         H I F . .
-        let perm = make_rotate_permutation(1, 1, 5, 3, 'R');
-        assert_eq!((0, 0), from_row_major(perm.apply(to_row_major(1, 0, 5)), 5));
-        assert_eq!((1, 0), from_row_major(perm.apply(to_row_major(2, 0, 5)), 5));
-        assert_eq!((2, 0), from_row_major(perm.apply(to_row_major(2, 1, 5)), 5));
-        assert_eq!((0, 1), from_row_major(perm.apply(to_row_major(0, 0, 5)), 5));
-        assert_eq!((1, 1), from_row_major(perm.apply(to_row_major(1, 1, 5)), 5));
-        assert_eq!((2, 1), from_row_major(perm.apply(to_row_major(2, 2, 5)), 5));
-        assert_eq!((0, 2), from_row_major(perm.apply(to_row_major(0, 1, 5)), 5));
-        assert_eq!((1, 2), from_row_major(perm.apply(to_row_major(0, 2, 5)), 5));
-        assert_eq!((2, 2), from_row_major(perm.apply(to_row_major(1, 2, 5)), 5));
+    fn test_rotation() {
+        let grid = Grid::parse("ABC\nDEF\nGHI").unwrap();
+        assert_eq!("DAB\nGEC\nHIF", decode(&grid, &['R'], 1).to_string());
+        assert_eq!("BCF\nAEI\nDGH", decode(&grid, &['L'], 1).to_string());
+        assert_eq!(grid, decode(&grid, &['R'], 8));
     }
 
     #[test]
-    fn test_make_rotate_permutation_left() {
-        let perm = make_rotate_permutation(1, 1, 5, 3, 'L');
-        assert_eq!((1, 0), from_row_major(perm.apply(to_row_major(0, 0, 5)), 5));
-        assert_eq!((2, 0), from_row_major(perm.apply(to_row_major(1, 0, 5)), 5));
-        assert_eq!((2, 1), from_row_major(perm.apply(to_row_major(2, 0, 5)), 5));
-        assert_eq!((0, 0), from_row_major(perm.apply(to_row_major(0, 1, 5)), 5));
-        assert_eq!((1, 1), from_row_major(perm.apply(to_row_major(1, 1, 5)), 5));
-        assert_eq!((2, 2), from_row_major(perm.apply(to_row_major(2, 1, 5)), 5));
-        assert_eq!((0, 1), from_row_major(perm.apply(to_row_major(0, 2, 5)), 5));
-        assert_eq!((0, 2), from_row_major(perm.apply(to_row_major(1, 2, 5)), 5));
-        assert_eq!((1, 2), from_row_major(perm.apply(to_row_major(2, 2, 5)), 5));
+    fn test_invalid_key() {
+        assert_eq!(
+            "line 1, col 2: expected L or R, found 'X'",
+            solve_part_1("LX\n\n>A<\n...\n...").unwrap_err().to_string()
+        );
     }
 }
@@ -1,24 +1,20 @@
 use std::cmp::max;
 use std::collections::{HashMap, HashSet, VecDeque};
 
-use array2d::Array2D;
+use crate::geometry::Dir4;
+use crate::geometry::Point;
+use crate::grid::Grid;
+use crate::solver::SolveError;
 
-pub fn solve_part_1(input: &str) -> String {
-    let width = input.lines().next().unwrap().chars().count();
-    let height = input.lines().count();
-    let mut grid = Array2D::<char>::filled_with(' ', width, height);
-    let mut start_pos = (0, 0);
-    for (y, line) in input.lines().enumerate() {
-        for (x, ch) in line.chars().enumerate() {
-            grid[(x, y)] = ch;
-            if ch == 'S' {
-                start_pos = (x, y);
-            }
-        }
-    }
-    const DIRECTIONS: [(isize, isize); 4] = [(-1, 0), (0, -1), (1, 0), (0, 1)];
-    let mut states: Vec<_> = (0..4)
-        .map(|dir| (start_pos.0, start_pos.1, 1000, dir))
+pub fn solve_part_1(input: &str) -> Result<String, SolveError> {
+    let grid = Grid::parse(input)?;
+    let (width, height) = (grid.columns(), grid.rows());
+    let start_pos = grid
+        .find(&'S')
+        .ok_or_else(|| SolveError::new("no start in the map"))?;
+    let mut states: Vec<_> = Dir4::ALL
+        .into_iter()
+        .map(|dir| (start_pos.x, start_pos.y, 1000, dir))
         .collect();
     let mut visited_states = HashSet::<_>::new();
     for _ in 0..100 {
@@ -28,19 +24,20 @@ pub fn solve_part_1(input: &str) -> String {
                 continue;
             }
             visited_states.insert((x, y, z, dir));
-            for turn in -1..=1 {
-                let new_dir = (((dir as isize) + turn + 4) % 4) as usize;
-                let (dx, dy) = DIRECTIONS[new_dir];
-                if let (Some(nx), Some(ny)) = (x.checked_add_signed(dx), y.checked_add_signed(dy)) {
-                    if nx >= width || ny >= height || grid[(nx, ny)] == '#' {
+            for new_dir in [dir.turn_left(), dir, dir.turn_right()] {
+                let step = new_dir.delta::<isize>();
+                if let (Some(nx), Some(ny)) =
+                    (x.checked_add_signed(step.x), y.checked_add_signed(step.y))
+                {
+                    if nx >= width || ny >= height || grid[Point::new(nx, ny)] == '#' {
                         continue;
                     }
-                    let new_z = match grid[(nx, ny)] {
+                    let new_z = match grid[Point::new(nx, ny)] {
                         '+' => z + 1,
                         '.' => z - 1,
                         'S' => z - 1,
                         '-' => z - 2,
-                        _ => unreachable!("{nx} {ny} {}", grid[(nx, ny)]),
+                        _ => unreachable!("{nx} {ny} {}", grid[Point::new(nx, ny)]),
                     };
                     next_states.push((nx, ny, new_z, new_dir));
                 }
@@ -48,30 +45,23 @@ pub fn solve_part_1(input: &str) -> String {
         }
         states = next_states;
     }
-    states
+    Ok(states
         .into_iter()
         .map(|(_, _, z, _)| z)
         .max()
         .unwrap()
-        .to_string()
+        .to_string())
 }
 
-pub fn solve_part_2(input: &str) -> String {
-    let width = input.lines().next().unwrap().chars().count();
-    let height = input.lines().count();
-    let mut grid = Array2D::<char>::filled_with(' ', width, height);
-    let mut start_pos = (0, 0);
-    for (y, line) in input.lines().enumerate() {
-        for (x, ch) in line.chars().enumerate() {
-            grid[(x, y)] = ch;
-            if ch == 'S' {
-                start_pos = (x, y);
-            }
-        }
-    }
-    const DIRECTIONS: [(isize, isize); 4] = [(-1, 0), (0, -1), (1, 0), (0, 1)];
-    let mut states: Vec<_> = (0..4)
-        .map(|dir| (start_pos.0, start_pos.1, 10000, dir, 0))
+pub fn solve_part_2(input: &str) -> Result<String, SolveError> {
+    let grid = Grid::parse(input)?;
+    let (width, height) = (grid.columns(), grid.rows());
+    let start_pos = grid
+        .find(&'S')
+        .ok_or_else(|| SolveError::new("no start in the map"))?;
+    let mut states: Vec<_> = Dir4::ALL
+        .into_iter()
+        .map(|dir| (start_pos.x, start_pos.y, 10000, dir, 0))
         .collect();
     let mut visited_states = HashSet::<_>::new();
     let mut t = 0;
@@ -82,17 +72,18 @@ pub fn solve_part_2(input: &str) -> String {
                 continue;
             }
             visited_states.insert((x, y, z, dir, checkpoints));
-            if x == start_pos.0 && y == start_pos.1 && z >= 10000 && checkpoints >= 3 {
-                return t.to_string();
+            if x == start_pos.x && y == start_pos.y && z >= 10000 && checkpoints >= 3 {
+                return Ok(t.to_string());
             }
-            for turn in -1..=1 {
-                let new_dir = (((dir as isize) + turn + 4) % 4) as usize;
-                let (dx, dy) = DIRECTIONS[new_dir];
-                if let (Some(nx), Some(ny)) = (x.checked_add_signed(dx), y.checked_add_signed(dy)) {
-                    if nx >= width || ny >= height || grid[(nx, ny)] == '#' {
+            for new_dir in [dir.turn_left(), dir, dir.turn_right()] {
+                let step = new_dir.delta::<isize>();
+                if let (Some(nx), Some(ny)) =
+                    (x.checked_add_signed(step.x), y.checked_add_signed(step.y))
+                {
+                    if nx >= width || ny >= height || grid[Point::new(nx, ny)] == '#' {
                         continue;
                     }
-                    let new_checkpoints = match grid[(nx, ny)] {
+                    let new_checkpoints = match grid[Point::new(nx, ny)] {
                         'A' => max(checkpoints, 1),
                         'B' => max(checkpoints, 2),
                         'C' => max(checkpoints, 3),
@@ -101,11 +92,11 @@ pub fn solve_part_2(input: &str) -> String {
                     if new_checkpoints - checkpoints > 1 {
                         continue;
                     }
-                    let new_z = match grid[(nx, ny)] {
+                    let new_z = match grid[Point::new(nx, ny)] {
                         '+' => z + 1,
                         '.' | 'S' | 'A' | 'B' | 'C' => z - 1,
                         '-' => z - 2,
-                        _ => unreachable!("{nx} {ny} {}", grid[(nx, ny)]),
+                        _ => unreachable!("{nx} {ny} {}", grid[Point::new(nx, ny)]),
                     };
                     next_states.push((nx, ny, new_z, new_dir, new_checkpoints));
                 }
@@ -120,12 +111,10 @@ fn find_best_route_to_south(
     start_x: usize,
     start_y: usize,
     start_z: isize,
-    start_dir: usize,
-    grid: &Array2D<char>,
-) -> Vec<(usize, usize, usize, usize)> {
-    let height = grid.num_columns();
-    let width = grid.num_rows();
-    const DIRECTIONS: [(isize, isize); 4] = [(-1, 0), (0, -1), (1, 0), (0, 1)];
+    start_dir: Dir4,
+    grid: &Grid<char>,
+) -> Vec<(usize, usize, Dir4, usize)> {
+    let (width, height) = (grid.columns(), grid.rows());
     let mut states = VecDeque::new();
     let mut visited_positions = HashMap::new();
     let mut max_y = start_y;
@@ -148,22 +137,22 @@ fn find_best_route_to_south(
             }
             continue;
         }
-        for turn in -1..=1 {
-            let new_dir = (((dir as isize) + turn + 4) % 4) as usize;
-            let (dx, dy) = DIRECTIONS[new_dir];
-            if let (Some(nx), Some(ny_global)) =
-                (x.checked_add_signed(dx), y_global.checked_add_signed(dy))
-            {
+        for new_dir in [dir.turn_left(), dir, dir.turn_right()] {
+            let step = new_dir.delta::<isize>();
+            if let (Some(nx), Some(ny_global)) = (
+                x.checked_add_signed(step.x),
+                y_global.checked_add_signed(step.y),
+            ) {
                 let ny_local = ny_global % height;
-                if nx >= width || grid[(nx, ny_local)] == '#' {
+                if nx >= width || grid[Point::new(nx, ny_local)] == '#' {
                     continue;
                 }
-                let new_z = match grid[(nx, ny_local)] {
+                let new_z = match grid[Point::new(nx, ny_local)] {
                     '+' => z + 1,
                     '.' => z - 1,
                     'S' => z - 1,
                     '-' => z - 2,
-                    _ => unreachable!("{nx} {ny_local} {}", grid[(nx, ny_local)]),
+                    _ => unreachable!("{nx} {ny_local} {}", grid[Point::new(nx, ny_local)]),
                 };
                 if new_z < 0 {
                     continue;
@@ -190,28 +179,22 @@ fn find_best_route_to_south(
     route
 }
 
-pub fn solve_part_3(input: &str) -> String {
-    let width = input.lines().next().unwrap().chars().count();
-    let height = input.lines().count();
-    let mut grid = Array2D::<char>::filled_with(' ', width, height);
-    let mut start_pos = (0, 0);
-    for (y, line) in input.lines().enumerate() {
-        for (x, ch) in line.chars().enumerate() {
-            grid[(x, y)] = ch;
-            if ch == 'S' {
-                start_pos = (x, y);
-            }
-        }
-    }
+pub fn solve_part_3(input: &str) -> Result<String, SolveError> {
+    let grid = Grid::parse(input)?;
+    let height = grid.rows();
+    let start_pos = grid
+        .find(&'S')
+        .ok_or_else(|| SolveError::new("no start in the map"))?;
     const START_Z: usize = 384400;
     // Find a route that has a height-aligned cycle. Since we don't know at which altitude this will happen, search for
     // the altitude exponentially.
     let mut start_z = 10;
     let possible_routes_with_cycles;
     loop {
-        let routes: Vec<_> = (0..4)
+        let routes: Vec<_> = Dir4::ALL
+            .into_iter()
             .map(|start_dir| {
-                find_best_route_to_south(start_pos.0, start_pos.1, start_z, start_dir, &grid)
+                find_best_route_to_south(start_pos.x, start_pos.y, start_z, start_dir, &grid)
             })
             .collect();
         let mut result = Vec::new();
@@ -251,7 +234,7 @@ pub fn solve_part_3(input: &str) -> String {
             find_best_route_to_south(x_end, y_suffix, z_suffix as isize, dir_end, &grid);
         best_y = max(best_y, remaining_route.last().unwrap().1);
     }
-    (best_y).to_string()
+    Ok(best_y.to_string())
 }
 
 #[cfg(test)]
@@ -272,6 +255,7 @@ mod tests {
 #.+-.+.++.#
 #.........#"
             )
+            .unwrap()
         );
     }
 
@@ -289,6 +273,7 @@ mod tests {
 #.+.B.+.#
 #########"
             )
+            .unwrap()
         );
 
         assert_eq!(
@@ -312,6 +297,7 @@ mod tests {
 #...+.-+..##+..+B.+.#-+-++..--#
 ###############################"
             )
+            .unwrap()
         );
         assert_eq!(
             "206",
@@ -336,6 +322,7 @@ mod tests {
 #-----------------------------#
 ###############################"
             )
+            .unwrap()
         );
     }
 }
@@ -1,104 +1,51 @@
-use array2d::Array2D;
+use crate::geometry::Dir4;
+use crate::geometry::Dir8;
+use crate::geometry::Point;
+use crate::grid::Grid;
+use crate::solver::SolveError;
 
-pub fn solve_part_1(input: &str) -> String {
-    let lines: Vec<&str> = input.lines().collect();
-    let width = lines.len();
-    let height = lines[0].len();
-    let mut depths = Array2D::filled_with(0, width, height);
-    let mut amount_dug_out = 0;
-    for i in 0..width {
-        for j in 0..height {
-            if lines[i][j..j + 1] == *"#" {
-                depths[(i, j)] = 1;
-                amount_dug_out += 1;
-            }
-        }
-    }
+/// Digs the ground level by level, where a block can be dug one level deeper if none
+/// of the blocks at the offsets is more than a level shallower. Blocks outside the
+/// map are at level 0.
+fn dig(input: &str, offsets: &[Point<isize>]) -> Result<String, SolveError> {
+    let mut depths = Grid::parse(input)?.map(|&c| if c == '#' { 1 } else { 0 });
+    let mut amount_dug_out = depths.iter().filter(|&(_, &depth)| depth == 1).count();
     let mut level = 2;
     loop {
-        let mut additional_amount_dug_out = 0;
-        for i in 0..width {
-            for j in 0..height {
-                if depths[(i, j)] != level - 1 {
-                    continue;
-                }
-                if level - depths[(i - 1, j)] > 1
-                    || level - depths[(i + 1, j)] > 1
-                    || level - depths[(i, j - 1)] > 1
-                    || level - depths[(i, j + 1)] > 1
-                {
-                    continue;
-                }
-                additional_amount_dug_out += 1;
-                depths[(i, j)] = level;
-            }
-        }
-        if additional_amount_dug_out == 0 {
+        let to_dig: Vec<Point<usize>> = depths
+            .iter()
+            .filter(|&(point, &depth)| {
+                depth == level - 1
+                    && offsets.iter().all(|&offset| {
+                        depths
+                            .step(point, offset)
+                            .is_some_and(|next| level - depths[next] <= 1)
+                    })
+            })
+            .map(|(point, _)| point)
+            .collect();
+        if to_dig.is_empty() {
             break;
         }
-        amount_dug_out += additional_amount_dug_out;
+        amount_dug_out += to_dig.len();
+        for point in to_dig {
+            depths[point] = level;
+        }
         level += 1;
     }
-    amount_dug_out.to_string()
+    Ok(amount_dug_out.to_string())
 }
 
-pub fn solve_part_2(input: &str) -> String {
+pub fn solve_part_1(input: &str) -> Result<String, SolveError> {
+    dig(input, &Dir4::ALL.map(Point::from))
+}
+
+pub fn solve_part_2(input: &str) -> Result<String, SolveError> {
     solve_part_1(input)
 }
 
-pub fn solve_part_3(input: &str) -> String {
-    let lines: Vec<&str> = input.lines().collect();
-    let width = lines.len();
-    let height = lines[0].len();
-    let mut depths = Array2D::filled_with(0, width, height);
-    let mut amount_dug_out = 0;
-    for i in 0..width {
-        for j in 0..height {
-            if lines[i][j..j + 1] == *"#" {
-                depths[(i, j)] = 1;
-                amount_dug_out += 1;
-            }
-        }
-    }
-    let mut level = 2;
-    loop {
-        let mut additional_amount_dug_out = 0;
-        for i in 0..width {
-            for j in 0..height {
-                if depths[(i, j)] != level - 1 {
-                    continue;
-                }
-                let mut can_dig = true;
-                for check_i in ((i as isize) - 1)..(i as isize + 2) {
-                    for check_j in ((j as isize) - 1)..(j as isize + 2) {
-                        if check_i < 0 || check_j < 0 {
-                            can_dig = false;
-                            continue;
-                        }
-                        let check_i = check_i as usize;
-                        let check_j = check_j as usize;
-                        if check_i >= width || check_j >= height {
-                            can_dig = false;
-                            continue;
-                        }
-                        if level - depths[(check_i, check_j)] > 1 {
-                            can_dig = false;
-                        }
-                    }
-                }
-                if can_dig {
-                    additional_amount_dug_out += 1;
-                    depths[(i, j)] = level;
-                }
-            }
-        }
-        if additional_amount_dug_out == 0 {
-            break;
-        }
-        amount_dug_out += additional_amount_dug_out;
-        level += 1;
-    }
-    amount_dug_out.to_string()
+pub fn solve_part_3(input: &str) -> Result<String, SolveError> {
+    dig(input, &Dir8::ALL.map(Point::from))
 }
 
 #[cfg(test)]
@@ -120,6 +67,7 @@ mod tests {
 ...####...
 .........."
             )
+            .unwrap()
         );
     }
 
@@ -136,6 +84,7 @@ mod tests {
 ...####...
 .........."
             )
+            .unwrap()
         );
     }
 }
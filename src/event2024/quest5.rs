@@ -1,93 +1,127 @@
 use std::collections::HashMap;
 use std::collections::HashSet;
 
+use crate::quest::Quest;
+use crate::solver::Answer;
+use crate::solver::SolveError;
+use crate::trace;
 use crate::util::concatenate_numbers;
 
 fn simulate_one_round(columns: &mut [Vec<usize>], round: usize) {
     let columns_count = columns.len();
     let clapper = columns[round % columns_count].remove(0);
-    let target_column = &mut columns[(round + 1) % columns_count];
+    let target = (round + 1) % columns_count;
+    let target_column = &mut columns[target];
     let mut effective_clapper = clapper % (2 * target_column.len());
     if effective_clapper == 0 {
         effective_clapper = 2 * target_column.len();
     }
-    if effective_clapper <= target_column.len() {
-        target_column.insert(effective_clapper - 1, clapper);
+    let position = if effective_clapper <= target_column.len() {
+        effective_clapper - 1
     } else {
-        target_column.insert(2 * target_column.len() - effective_clapper + 1, clapper);
+        2 * target_column.len() - effective_clapper + 1
+    };
+    target_column.insert(position, clapper);
+    if trace::enabled() {
+        trace::moved(
+            &format!("clapper {clapper}"),
+            round as u64,
+            (target as i64, position as i64),
+        );
     }
 }
 
-fn parse_input(input: &str) -> Vec<Vec<usize>> {
+fn parse_input(input: &str) -> Result<Vec<Vec<usize>>, SolveError> {
     let mut columns: Vec<Vec<usize>> = Vec::new();
     for line in input.lines() {
         for (i, number) in line.split(" ").enumerate() {
             if columns.len() <= i {
                 columns.resize_with(i + 1, Vec::new);
             }
-            columns[i].push(number.parse().unwrap());
+            columns[i].push(number.parse().map_err(|_| {
+                SolveError::at_fragment(
+                    input,
+                    number,
+                    format!("expected a number, found {number:?}"),
+                )
+            })?);
         }
     }
-    columns
+    Ok(columns)
 }
 
-pub fn solve_part_1(input: &str) -> String {
-    let mut columns = parse_input(input);
-    for round in 0..10 {
-        simulate_one_round(&mut columns, round);
+/// The columns of dancers, parsed once for all the parts.
+pub struct Dance;
+
+impl Quest for Dance {
+    type Model = Vec<Vec<usize>>;
+
+    fn parse(input: &str) -> Result<Vec<Vec<usize>>, SolveError> {
+        parse_input(input)
     }
-    columns
-        .iter()
-        .map(|c| c[0].to_string())
-        .collect::<Vec<String>>()
-        .join("")
-        .to_string()
-}
 
-pub fn solve_part_2(input: &str) -> String {
-    let mut columns = parse_input(input);
-    let mut number_counts: HashMap<usize, usize> = HashMap::new();
-    let mut round = 0;
-    loop {
-        simulate_one_round(&mut columns, round);
-        let number = columns
-            .iter()
-            .fold(0, |res, column| concatenate_numbers(res, column[0]));
-        let mut count = *number_counts.get(&number).unwrap_or(&0);
-        count += 1;
-        if count == 2024 {
-            return (number * (round + 1)).to_string();
+    fn part_1(model: &Vec<Vec<usize>>) -> Result<Answer, SolveError> {
+        let mut columns = model.clone();
+        for round in 0..10 {
+            simulate_one_round(&mut columns, round);
         }
-        number_counts.insert(number, count);
-        round += 1;
+        Ok(Answer::from(
+            columns
+                .iter()
+                .map(|c| c[0].to_string())
+                .collect::<Vec<String>>()
+                .join(""),
+        ))
     }
-}
 
-pub fn solve_part_3(input: &str) -> String {
-    let mut columns = parse_input(input);
-    let mut states: HashSet<Vec<Vec<usize>>> = HashSet::new();
-    let mut round = 0;
-    let mut max: usize = 0;
-    loop {
-        simulate_one_round(&mut columns, round);
-        let number = columns
-            .iter()
-            .fold(0, |res, column| concatenate_numbers(res, column[0]));
-        if max < number {
-            max = number;
+    fn part_2(model: &Vec<Vec<usize>>) -> Result<Answer, SolveError> {
+        let mut columns = model.clone();
+        let mut number_counts: HashMap<usize, usize> = HashMap::new();
+        let mut round = 0;
+        loop {
+            simulate_one_round(&mut columns, round);
+            let number = columns
+                .iter()
+                .fold(0, |res, column| concatenate_numbers(res, column[0]));
+            let mut count = *number_counts.get(&number).unwrap_or(&0);
+            count += 1;
+            if count == 2024 {
+                return Ok(Answer::from(number * (round + 1)));
+            }
+            number_counts.insert(number, count);
+            round += 1;
         }
-        if states.contains(&columns) {
-            break;
+    }
+
+    fn part_3(model: &Vec<Vec<usize>>) -> Result<Answer, SolveError> {
+        let mut columns = model.clone();
+        let mut states: HashSet<Vec<Vec<usize>>> = HashSet::new();
+        let mut round = 0;
+        let mut max: usize = 0;
+        loop {
+            simulate_one_round(&mut columns, round);
+            let number = columns
+                .iter()
+                .fold(0, |res, column| concatenate_numbers(res, column[0]));
+            if max < number {
+                max = number;
+            }
+            if states.contains(&columns) {
+                break;
+            }
+            states.insert(columns.clone());
+            trace::count("states", round as u64, states.len());
+            round += 1;
         }
-        states.insert(columns.clone());
-        round += 1;
+        Ok(Answer::from(max))
     }
-    max.to_string()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::quest::solve;
+    use crate::types::Part;
 
     use test_log::test;
 
@@ -95,12 +129,15 @@ mod tests {
     fn test_solve_part_1() {
         assert_eq!(
             "2323",
-            solve_part_1(
+            solve::<Dance>(
+                Part::One,
                 "2 3 4 5
 3 4 5 2
 4 5 2 3
 5 2 3 4"
             )
+            .unwrap()
+            .to_string()
         );
     }
 
@@ -108,10 +145,13 @@ mod tests {
     fn test_solve_part_2() {
         assert_eq!(
             "50877075",
-            solve_part_2(
+            solve::<Dance>(
+                Part::Two,
                 "2 3 4 5
 6 7 8 9"
             )
+            .unwrap()
+            .to_string()
         );
     }
 
@@ -164,14 +204,27 @@ mod tests {
         assert_eq!(vec![vec![2], vec![3, 4, 5, 6, 15, 7]], columns);
     }
 
+    #[test]
+    fn test_invalid_input() {
+        assert_eq!(
+            "line 2, col 3: expected a number, found \"x\"",
+            solve::<Dance>(Part::One, "2 3\n6 x")
+                .unwrap_err()
+                .to_string()
+        );
+    }
+
     #[test]
     fn test_solve_part_3() {
         assert_eq!(
             "6584",
-            solve_part_3(
+            solve::<Dance>(
+                Part::Three,
                 "2 3 4 5
 6 7 8 9"
             )
+            .unwrap()
+            .to_string()
         );
     }
 }
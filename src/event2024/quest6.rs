@@ -6,16 +6,17 @@ use petgraph::{
     visit::{Control, DfsEvent::TreeEdge, depth_first_search},
 };
 
-pub fn solve_part_1(input: &str) -> String {
-    let nodes: Vec<(&str, Vec<&str>)> = input
-        .lines()
-        .map(|line| {
-            let mut splits = line.splitn(2, ":");
-            let node = splits.next().unwrap();
-            let edges: Vec<&str> = splits.next().unwrap().split(",").collect();
-            (node, edges)
-        })
-        .collect();
+use crate::parse::Input;
+use crate::solver::SolveError;
+
+/// The branches of the tree: each node with the nodes it leads to.
+fn parse_input(input: &str) -> Result<Vec<(&str, Vec<&str>)>, SolveError> {
+    let input = Input::new(input);
+    input.lines().map(|line| input.adjacency(line)).collect()
+}
+
+pub fn solve_part_1(input: &str) -> Result<String, SolveError> {
+    let nodes = parse_input(input)?;
     let mut graph: petgraph::Graph<&str, (), petgraph::Directed, usize> = Graph::default();
     let mut node_to_idx = HashMap::new();
     nodes.iter().for_each(|node| {
@@ -54,23 +55,15 @@ pub fn solve_part_1(input: &str) -> String {
         .exactly_one()
         .unwrap()
         .0;
-    node_paths
+    Ok(node_paths
         .into_values()
         .filter(|p| p.ends_with("@") && p.len() == unique_len)
         .exactly_one()
-        .unwrap()
+        .unwrap())
 }
 
-pub fn solve_part_2(input: &str) -> String {
-    let nodes: Vec<(&str, Vec<&str>)> = input
-        .lines()
-        .map(|line| {
-            let mut splits = line.splitn(2, ":");
-            let node = splits.next().unwrap();
-            let edges: Vec<&str> = splits.next().unwrap().split(",").collect();
-            (node, edges)
-        })
-        .collect();
+pub fn solve_part_2(input: &str) -> Result<String, SolveError> {
+    let nodes = parse_input(input)?;
     let mut graph: petgraph::Graph<&str, (), petgraph::Directed, usize> = Graph::default();
     let mut node_to_idx = HashMap::new();
     nodes.iter().for_each(|node| {
@@ -119,17 +112,17 @@ pub fn solve_part_2(input: &str) -> String {
         .exactly_one()
         .unwrap()
         .0;
-    node_paths
+    Ok(node_paths
         .into_values()
         .filter(|p| p.last().unwrap() == "@" && p.len() == unique_len)
         .exactly_one()
         .unwrap()
         .iter()
         .map(|n| n.chars().next().unwrap())
-        .join("")
+        .join(""))
 }
 
-pub fn solve_part_3(input: &str) -> String {
+pub fn solve_part_3(input: &str) -> Result<String, SolveError> {
     solve_part_2(input)
 }
 
@@ -153,6 +146,7 @@ F:@
 G:@
 H:@"
             )
+            .unwrap()
         );
     }
 
@@ -171,6 +165,7 @@ F:@
 G:@
 H:@"
             )
+            .unwrap()
         );
     }
 }
@@ -1,5 +1,9 @@
 use itertools::Itertools;
 
+use crate::solver::Param;
+use crate::solver::Params;
+use crate::solver::SolveError;
+
 pub fn solve_part_1(input: &str) -> String {
     let mut devices: Vec<_> = input
         .lines()
@@ -28,9 +32,14 @@ pub fn solve_part_1(input: &str) -> String {
     devices.iter().map(|d| d.0.chars().next().unwrap()).join("")
 }
 
-pub fn solve_part_2(input: &str) -> String {
-    let track = "-=++=-==++=++=-=+=-=+=+=--=-=++=-==++=-+=-=+=-=+=+=++=-+==++=++=-=-=---=++==--+++==++=+=--==++==+++=++=+++=--=+=-=+=-+=-+=-+-=+=-=+=-+++=+==++++==---=+=+=-S";
-    solve_part_2_with_track(input, track)
+/// The track of part 2, flattened to a single loop starting after S.
+pub const TRACK: Param<String> = Param::new(
+    "track",
+    "-=++=-==++=++=-=+=-=+=+=--=-=++=-==++=-+=-=+=-=+=+=++=-+==++=++=-=-=---=++==--+++==++=+=--==++==+++=++=+++=--=+=-=+=-+=-+=-+-=+=-=+=-+++=+==++++==---=+=+=-S",
+);
+
+pub fn solve_part_2(input: &str, params: &Params) -> Result<String, SolveError> {
+    Ok(solve_part_2_with_track(input, params.get(&TRACK)?.as_str()))
 }
 
 fn solve_part_2_with_track(input: &str, track: &str) -> String {
@@ -230,6 +239,16 @@ D:=,=,=,+",
                 "+===++-=+=-S"
             )
         );
+        assert_eq!(
+            Ok(String::from("DCBA")),
+            solve_part_2(
+                "A:+,-,=,=
+B:+,=,-,+
+C:=,-,+,+
+D:=,=,=,+",
+                &Params::from_iter([("track", "+===++-=+=-S")])
+            )
+        );
     }
 
     #[test]
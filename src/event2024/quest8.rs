@@ -1,5 +1,9 @@
 use std::cmp;
 
+use crate::solver::Param;
+use crate::solver::Params;
+use crate::solver::SolveError;
+
 pub fn solve_part_1(input: &str) -> String {
     let available_blocks: i64 = input.parse().unwrap();
     let pyramid_size_index = available_blocks.isqrt() + 1;
@@ -26,8 +30,15 @@ fn solve_part_2_with_params(input: &str, acolytes: i64, blocks_available: i64) -
     }
 }
 
-pub fn solve_part_2(input: &str) -> String {
-    solve_part_2_with_params(input, 1111, 20240000)
+pub const PART_2_ACOLYTES: Param<i64> = Param::new("acolytes", "1111");
+pub const PART_2_BLOCKS: Param<i64> = Param::new("blocks", "20240000");
+
+pub fn solve_part_2(input: &str, params: &Params) -> Result<String, SolveError> {
+    Ok(solve_part_2_with_params(
+        input,
+        params.get(&PART_2_ACOLYTES)?,
+        params.get(&PART_2_BLOCKS)?,
+    ))
 }
 
 fn solve_part_3_with_params(input: &str, acolytes: i64, blocks_available: i64) -> String {
@@ -64,8 +75,15 @@ fn solve_part_3_with_params(input: &str, acolytes: i64, blocks_available: i64) -
     }
 }
 
-pub fn solve_part_3(input: &str) -> String {
-    solve_part_3_with_params(input, 10, 202400000)
+pub const PART_3_ACOLYTES: Param<i64> = Param::new("acolytes", "10");
+pub const PART_3_BLOCKS: Param<i64> = Param::new("blocks", "202400000");
+
+pub fn solve_part_3(input: &str, params: &Params) -> Result<String, SolveError> {
+    Ok(solve_part_3_with_params(
+        input,
+        params.get(&PART_3_ACOLYTES)?,
+        params.get(&PART_3_BLOCKS)?,
+    ))
 }
 
 #[cfg(test)]
@@ -82,6 +100,8 @@ mod tests {
     #[test]
     fn test_solve_part_2() {
         assert_eq!("27", solve_part_2_with_params("3", 5, 50));
+        let params = Params::from_iter([("acolytes", "5"), ("blocks", "50")]);
+        assert_eq!(Ok(String::from("27")), solve_part_2("3", &params));
     }
 
     #[test]
@@ -1,65 +1,62 @@
-pub fn solve_part_1(input: &str) -> String {
-    let (names, instructions) = input.split_once("\n\n").unwrap();
+use crate::parse::Input;
+use crate::solver::SolveError;
+
+/// A move to the left or right by some steps.
+type Move = (char, usize);
+
+/// The names, and the moves to make among them.
+fn parse_input(input: &str) -> Result<(Vec<&str>, Vec<Move>), SolveError> {
+    let input = Input::new(input);
+    let (names, instructions) = input.two_sections()?;
     let names: Vec<&str> = names.split(",").collect();
-    let instructions: Vec<&str> = instructions.split(",").collect();
+    let instructions = input.instructions(instructions)?;
+    if let Some((direction, _)) = instructions.iter().find(|(d, _)| !matches!(d, 'L' | 'R')) {
+        return Err(SolveError::new(format!(
+            "expected L or R, found direction {direction:?}"
+        )));
+    }
+    Ok((names, instructions))
+}
+
+pub fn solve_part_1(input: &str) -> Result<String, SolveError> {
+    let (names, instructions) = parse_input(input)?;
     let mut ptr = 0usize;
-    for instruction in instructions {
-        if let Some(left_shift_str) = instruction.strip_prefix("L") {
-            let left_shift: usize = left_shift_str.parse().unwrap();
-            ptr = ptr.saturating_sub(left_shift);
-            continue;
-        }
-        if let Some(right_shift_str) = instruction.strip_prefix("R") {
-            let right_shift: usize = right_shift_str.parse().unwrap();
-            ptr += right_shift;
+    for (direction, shift) in instructions {
+        if direction == 'L' {
+            ptr = ptr.saturating_sub(shift);
+        } else {
+            ptr += shift;
             ptr = ptr.min(names.len() - 1);
-            continue;
         }
     }
-    names[ptr].to_string()
+    Ok(names[ptr].to_string())
 }
 
-pub fn solve_part_2(input: &str) -> String {
-    let (names, instructions) = input.split_once("\n\n").unwrap();
-    let names: Vec<&str> = names.split(",").collect();
-    let instructions: Vec<&str> = instructions.split(",").collect();
+pub fn solve_part_2(input: &str) -> Result<String, SolveError> {
+    let (names, instructions) = parse_input(input)?;
     let mut ptr = 0;
-    for instruction in instructions {
-        if let Some(left_shift_str) = instruction.strip_prefix("L") {
-            let left_shift: usize = left_shift_str.parse().unwrap();
-            ptr += names.len() - left_shift;
-            ptr %= names.len();
-            continue;
-        }
-        if let Some(right_shift_str) = instruction.strip_prefix("R") {
-            let right_shift: usize = right_shift_str.parse().unwrap();
-            ptr += right_shift;
-            ptr %= names.len();
-            continue;
+    for (direction, shift) in instructions {
+        if direction == 'L' {
+            ptr += names.len() - shift;
+        } else {
+            ptr += shift;
         }
+        ptr %= names.len();
     }
-    names[ptr].to_string()
+    Ok(names[ptr].to_string())
 }
 
-pub fn solve_part_3(input: &str) -> String {
-    let (names, instructions) = input.split_once("\n\n").unwrap();
-    let mut names: Vec<&str> = names.split(",").collect();
-    let instructions: Vec<&str> = instructions.split(",").collect();
-    for instruction in instructions {
-        if let Some(left_shift_str) = instruction.strip_prefix("L") {
-            let left_shift: usize = left_shift_str.parse().unwrap();
-            let ptr = (names.len() - (left_shift % names.len())) % names.len();
-            names.swap(0, ptr);
-            continue;
-        }
-        if let Some(right_shift_str) = instruction.strip_prefix("R") {
-            let right_shift: usize = right_shift_str.parse().unwrap();
-            let ptr = right_shift % names.len();
-            names.swap(0, ptr);
-            continue;
-        }
+pub fn solve_part_3(input: &str) -> Result<String, SolveError> {
+    let (mut names, instructions) = parse_input(input)?;
+    for (direction, shift) in instructions {
+        let ptr = if direction == 'L' {
+            (names.len() - (shift % names.len())) % names.len()
+        } else {
+            shift % names.len()
+        };
+        names.swap(0, ptr);
     }
-    names[0].to_string()
+    Ok(names[0].to_string())
 }
 
 #[cfg(test)]
@@ -77,6 +74,7 @@ mod tests {
 
 R3,L2,R3,L1"
             )
+            .unwrap()
         );
     }
 
@@ -89,6 +87,7 @@ R3,L2,R3,L1"
 
 R3,L2,R3,L1"
             )
+            .unwrap()
         );
     }
 
@@ -101,6 +100,18 @@ R3,L2,R3,L1"
 
 R3,L2,R3,L3"
             )
+            .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_invalid_instruction() {
+        assert_eq!(
+            "line 3, col 5: expected integer, found \"x\"",
+            solve_part_1("Vyrdax,Drakzyph\n\nR3,Lx")
+                .unwrap_err()
+                .to_string()
         );
+        assert!(solve_part_1("Vyrdax,Drakzyph\n\nR3,U1").is_err());
     }
 }
@@ -1,157 +1,108 @@
 use std::collections::{BTreeSet, HashMap, HashSet};
 
-use itertools::Itertools;
+use crate::debug_with_rate;
+use crate::geometry::KNIGHT_MOVES;
+use crate::geometry::Point;
+use crate::grid::Grid;
+use crate::solver::Param;
+use crate::solver::Params;
+use crate::solver::SolveError;
 
-pub fn solve_part_1(input: &str) -> String {
-    let board: Vec<Vec<_>> = input.lines().map(|l| l.chars().collect()).collect();
-    let mut front: HashSet<_> = (0usize..board.len())
-        .cartesian_product(0usize..board[0].len())
-        .filter(|&(i, j)| board[i][j] == 'D')
-        .collect();
+/// The squares a knight can jump to without leaving the board.
+fn knight_moves(board: &Grid<char>, square: Point<usize>) -> impl Iterator<Item = Point<usize>> {
+    KNIGHT_MOVES
+        .into_iter()
+        .filter_map(move |offset| board.step(square, offset))
+}
+
+pub fn solve_part_1(input: &str) -> Result<String, SolveError> {
+    let board = Grid::parse(input)?;
+    let mut front: HashSet<_> = board.find_all(&'D').collect();
     let mut visited = HashSet::new();
-    let knight_moves: [(isize, isize); 8] = [
-        (2, 1),
-        (2, -1),
-        (-2, -1),
-        (-2, 1),
-        (1, 2),
-        (1, -2),
-        (-1, -2),
-        (-1, 2),
-    ];
     for _ in 0..=4 {
         let mut next_front = HashSet::new();
-        for (i, j) in front.drain() {
-            for (di, dj) in knight_moves {
-                let (ni, nj) = (i.wrapping_add_signed(di), j.wrapping_add_signed(dj));
-                if ni >= board.len() || nj >= board[0].len() {
-                    continue;
-                }
-                if visited.contains(&(ni, nj)) {
+        for square in front.drain() {
+            for next in knight_moves(&board, square) {
+                if visited.contains(&next) {
                     continue;
                 }
-                next_front.insert((ni, nj));
+                next_front.insert(next);
             }
-            visited.insert((i, j));
+            visited.insert(square);
         }
         front = next_front;
     }
-    visited
+    Ok(visited
         .drain()
-        .filter(|&(i, j)| board[i][j] == 'S')
+        .filter(|&square| board[square] == 'S')
         .count()
-        .to_string()
+        .to_string())
 }
 
-fn solve_part_2_with_turns(input: &str, turns: usize) -> String {
-    let board: Vec<Vec<_>> = input.lines().map(|l| l.chars().collect()).collect();
-    let mut front: HashSet<_> = (0usize..board.len())
-        .cartesian_product(0usize..board[0].len())
-        .filter(|&(i, j)| board[i][j] == 'D')
-        .collect();
-    let knight_moves: [(isize, isize); 8] = [
-        (2, 1),
-        (2, -1),
-        (-2, -1),
-        (-2, 1),
-        (1, 2),
-        (1, -2),
-        (-1, -2),
-        (-1, 2),
-    ];
+fn solve_part_2_with_turns(input: &str, turns: usize) -> Result<String, SolveError> {
+    let board = Grid::parse(input)?;
+    let mut front: HashSet<_> = board.find_all(&'D').collect();
     let mut eaten_sheep = HashSet::new();
     for turn in 0..=turns {
         let mut next_front = HashSet::new();
-        for (i, j) in front.drain() {
-            for (di, dj) in knight_moves {
-                let (ni, nj) = (i.wrapping_add_signed(di), j.wrapping_add_signed(dj));
-                if ni >= board.len() || nj >= board[0].len() {
-                    continue;
-                }
-                next_front.insert((ni, nj));
-            }
-            if board[i][j] != '#' {
-                if let Some(sheep_i) = (i + 1).checked_sub(turn)
-                    && board[sheep_i][j] == 'S'
+        for square in front.drain() {
+            next_front.extend(knight_moves(&board, square));
+            if board[square] != '#' {
+                if let Some(sheep_y) = (square.y + 1).checked_sub(turn)
+                    && board[Point::new(square.x, sheep_y)] == 'S'
                 {
-                    eaten_sheep.insert((sheep_i, j));
+                    eaten_sheep.insert(Point::new(square.x, sheep_y));
                 }
-                if let Some(sheep_i) = i.checked_sub(turn)
+                if let Some(sheep_y) = square.y.checked_sub(turn)
                     && turn != 0
-                    && board[sheep_i][j] == 'S'
+                    && board[Point::new(square.x, sheep_y)] == 'S'
                 {
-                    eaten_sheep.insert((sheep_i, j));
+                    eaten_sheep.insert(Point::new(square.x, sheep_y));
                 }
             }
         }
         front = next_front;
     }
-    eaten_sheep.len().to_string()
+    Ok(eaten_sheep.len().to_string())
 }
 
-pub fn solve_part_2(input: &str) -> String {
-    solve_part_2_with_turns(input, 20)
+pub const TURNS: Param<usize> = Param::new("turns", "20");
+
+pub fn solve_part_2(input: &str, params: &Params) -> Result<String, SolveError> {
+    solve_part_2_with_turns(input, params.get(&TURNS)?)
 }
-type VeryComplexType = HashMap<(usize, usize, usize, Vec<(usize, usize)>), usize>;
+type VeryComplexType = HashMap<(usize, Point<usize>, Vec<Point<usize>>), usize>;
 fn count_winning_sequences(
     turn: usize,
-    dragon: (usize, usize),
-    hiding_places: &HashSet<(usize, usize)>,
-    sheep: BTreeSet<(usize, usize)>,
-    height: usize,
-    width: usize,
+    dragon: Point<usize>,
+    hiding_places: &HashSet<Point<usize>>,
+    sheep: BTreeSet<Point<usize>>,
+    board: &Grid<char>,
     cache: &mut VeryComplexType,
 ) -> usize {
     if sheep.is_empty() {
         return 1;
     }
-    let cache_key = (
-        turn % 2,
-        dragon.0,
-        dragon.1,
-        sheep.iter().cloned().collect(),
-    );
+    let cache_key = (turn % 2, dragon, sheep.iter().cloned().collect());
     if let Some(result) = cache.get(&cache_key) {
         return *result;
     }
+    debug_with_rate!("{} positions cached", cache.len());
     if turn % 2 == 1 {
-        let knight_moves: [(isize, isize); 8] = [
-            (2, 1),
-            (2, -1),
-            (-2, -1),
-            (-2, 1),
-            (1, 2),
-            (1, -2),
-            (-1, -2),
-            (-1, 2),
-        ];
-        let (i, j) = dragon;
         let mut total = 0;
-        for (di, dj) in knight_moves {
-            let (ni, nj) = (i.wrapping_add_signed(di), j.wrapping_add_signed(dj));
-            if ni >= height || nj >= width {
-                continue;
-            }
-            if !hiding_places.contains(&(ni, nj)) && sheep.contains(&(ni, nj)) {
+        for next in knight_moves(board, dragon) {
+            if !hiding_places.contains(&next) && sheep.contains(&next) {
                 let mut new_sheep = sheep.clone();
-                new_sheep.remove(&(ni, nj));
-                total += count_winning_sequences(
-                    turn + 1,
-                    (ni, nj),
-                    hiding_places,
-                    new_sheep,
-                    height,
-                    width,
-                    cache,
-                );
+                new_sheep.remove(&next);
+                total +=
+                    count_winning_sequences(turn + 1, next, hiding_places, new_sheep, board, cache);
             } else {
                 total += count_winning_sequences(
                     turn + 1,
-                    (ni, nj),
+                    next,
                     hiding_places,
                     sheep.clone(),
-                    height,
-                    width,
+                    board,
                     cache,
                 );
             }
@@ -161,69 +112,36 @@ fn count_winning_sequences(
     } else {
         let mut sheep_moves_available = false;
         let mut total = 0;
-        for &(i, j) in sheep.iter() {
-            if dragon == (i + 1, j) && !hiding_places.contains(&(i + 1, j)) {
+        for &one_sheep in sheep.iter() {
+            let below = Point::new(one_sheep.x, one_sheep.y + 1);
+            if dragon == below && !hiding_places.contains(&below) {
                 continue;
             }
             sheep_moves_available = true;
-            if i == (height - 1) {
+            if below.y == board.rows() {
                 continue;
             }
             let mut new_sheep = sheep.clone();
-            new_sheep.remove(&(i, j));
-            new_sheep.insert((i + 1, j));
-            total += count_winning_sequences(
-                turn + 1,
-                dragon,
-                hiding_places,
-                new_sheep,
-                height,
-                width,
-                cache,
-            );
+            new_sheep.remove(&one_sheep);
+            new_sheep.insert(below);
+            total +=
+                count_winning_sequences(turn + 1, dragon, hiding_places, new_sheep, board, cache);
         }
         if !sheep_moves_available {
-            return count_winning_sequences(
-                turn + 1,
-                dragon,
-                hiding_places,
-                sheep,
-                height,
-                width,
-                cache,
-            );
+            return count_winning_sequences(turn + 1, dragon, hiding_places, sheep, board, cache);
         }
         cache.insert(cache_key, total);
         total
     }
 }
 
-pub fn solve_part_3(input: &str) -> String {
-    let board: Vec<Vec<_>> = input.lines().map(|l| l.chars().collect()).collect();
-    let dragon = (0usize..board.len())
-        .cartesian_product(0usize..board[0].len())
-        .filter(|&(i, j)| board[i][j] == 'D')
-        .exactly_one()
-        .unwrap();
-    let sheep = (0usize..board.len())
-        .cartesian_product(0usize..board[0].len())
-        .filter(|&(i, j)| board[i][j] == 'S')
-        .collect::<BTreeSet<_>>();
-    let hiding_places = (0usize..board.len())
-        .cartesian_product(0usize..board[0].len())
-        .filter(|&(i, j)| board[i][j] == '#')
-        .collect::<HashSet<_>>();
+pub fn solve_part_3(input: &str) -> Result<String, SolveError> {
+    let board = Grid::parse(input)?;
+    let dragon = board.find_unique(&'D')?;
+    let sheep = board.find_all(&'S').collect::<BTreeSet<_>>();
+    let hiding_places = board.find_all(&'#').collect::<HashSet<_>>();
     let mut cache = HashMap::new();
-    count_winning_sequences(
-        0,
-        dragon,
-        &hiding_places,
-        sheep,
-        board.len(),
-        board[0].len(),
-        &mut cache,
-    )
-    .to_string()
+    Ok(count_winning_sequences(0, dragon, &hiding_places, sheep, &board, &mut cache).to_string())
 }
 
 #[cfg(test)]
@@ -242,6 +160,7 @@ mod tests {
 #.#
 #D."
             )
+            .unwrap()
         );
         assert_eq!(
             "8",
@@ -252,6 +171,7 @@ mod tests {
 .##
 .D#"
             )
+            .unwrap()
         );
         assert_eq!(
             "44",
@@ -262,6 +182,7 @@ mod tests {
 .....
 ..D.."
             )
+            .unwrap()
         );
         assert_eq!(
             "4406",
@@ -273,6 +194,7 @@ mod tests {
 .####
 ##D.#"
             )
+            .unwrap()
         );
         assert_eq!(
             "13033988838",
@@ -283,6 +205,7 @@ mod tests {
 .#.#.
 #.D.#"
             )
+            .unwrap()
         );
     }
 
@@ -290,7 +213,7 @@ mod tests {
     fn test_solve_part_2() {
         assert_eq!(
             "27",
-            solve_part_2_with_turns(
+            solve_part_2(
                 "...SSS##.....
 .S#.##..S#SS.
 ..S.##.S#..S.
@@ -304,8 +227,9 @@ S.S..S..S###.
 ..#.##...S##.
 .#...#.S#...S
 SS...#.S.#S..",
-                3
+                &Params::from_iter([("turns", "3")])
             )
+            .unwrap()
         );
     }
 }
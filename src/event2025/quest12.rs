@@ -1,26 +1,24 @@
-use std::collections::{HashMap, HashSet};
+use std::collections::HashSet;
 
-use itertools::Itertools;
+use crate::geometry::Point;
+use crate::grid::Grid;
+use crate::progress::Progress;
+use crate::solver::SolveError;
+use crate::trace;
 
 fn explode_from_barrel(
-    map: &HashMap<(isize, isize), i8>,
-    mut exploded: HashSet<(isize, isize)>,
-    mut front: HashSet<(isize, isize)>,
-) -> HashSet<(isize, isize)> {
+    map: &Grid<u8>,
+    mut exploded: HashSet<Point<usize>>,
+    mut front: HashSet<Point<usize>>,
+) -> HashSet<Point<usize>> {
     while !front.is_empty() {
         let mut next_front = HashSet::new();
-        for (i, j) in front.drain() {
-            exploded.insert((i, j));
-            let my_size = map[&(i, j)];
-            for (di, dj) in [(-1, 0), (0, -1), (0, 1), (1, 0)] {
-                let (ni, nj) = (i + di, j + dj);
-                if exploded.contains(&(ni, nj)) {
-                    continue;
-                }
-                if let Some(neighour_size) = map.get(&(ni, nj))
-                    && *neighour_size <= my_size
-                {
-                    next_front.insert((ni, nj));
+        for point in front.drain() {
+            exploded.insert(point);
+            let my_size = map[point];
+            for neighbour in map.neighbours_4(point) {
+                if !exploded.contains(&neighbour) && map[neighbour] <= my_size {
+                    next_front.insert(neighbour);
                 }
             }
         }
@@ -29,90 +27,71 @@ fn explode_from_barrel(
     exploded
 }
 
-pub fn solve_part_1(input: &str) -> String {
-    let map: HashMap<(isize, isize), i8> = input
-        .lines()
-        .enumerate()
-        .flat_map(|(i, line)| {
-            line.chars()
-                .enumerate()
-                .map(move |(j, ch)| ((i as isize, j as isize), ch as i8 - '0' as i8))
-        })
-        .collect();
-    let exploded = HashSet::<(isize, isize)>::new();
-    let front: HashSet<(isize, isize)> = [(0, 0)].into_iter().collect();
-    explode_from_barrel(&map, exploded, front).len().to_string()
+pub fn solve_part_1(input: &str) -> Result<String, SolveError> {
+    let map = Grid::parse_digits(input)?;
+    let exploded = HashSet::<Point<usize>>::new();
+    let front: HashSet<Point<usize>> = [Point::new(0, 0)].into_iter().collect();
+    Ok(explode_from_barrel(&map, exploded, front).len().to_string())
 }
 
-pub fn solve_part_2(input: &str) -> String {
-    let map: HashMap<(isize, isize), i8> = input
-        .lines()
-        .enumerate()
-        .flat_map(|(i, line)| {
-            line.chars()
-                .enumerate()
-                .map(move |(j, ch)| ((i as isize, j as isize), ch as i8 - '0' as i8))
-        })
-        .collect();
-    let exploded = HashSet::<(isize, isize)>::new();
-    let max_i = map.keys().map(|(i, _)| *i).max().unwrap();
-    let max_j = map.keys().map(|(_, j)| *j).max().unwrap();
-    let front: HashSet<(isize, isize)> = [(0, 0), (max_i, max_j)].into_iter().collect();
-    explode_from_barrel(&map, exploded, front).len().to_string()
+pub fn solve_part_2(input: &str) -> Result<String, SolveError> {
+    let map = Grid::parse_digits(input)?;
+    let exploded = HashSet::<Point<usize>>::new();
+    let corner = map.size() - Point::new(1, 1);
+    let front: HashSet<Point<usize>> = [Point::new(0, 0), corner].into_iter().collect();
+    Ok(explode_from_barrel(&map, exploded, front).len().to_string())
 }
 
-pub fn solve_part_3(input: &str) -> String {
-    let map: HashMap<(isize, isize), i8> = input
-        .lines()
-        .enumerate()
-        .flat_map(|(i, line)| {
-            line.chars()
-                .enumerate()
-                .map(move |(j, ch)| ((i as isize, j as isize), ch as i8 - '0' as i8))
-        })
-        .collect();
-    let max_i = map.keys().map(|(i, _)| *i).max().unwrap();
-    let max_j = map.keys().map(|(_, j)| *j).max().unwrap();
-    let best_barrel = (0..=max_i)
-        .cartesian_product(0..=max_j)
-        .map(|(i, j)| {
-            ((i, j), {
-                let exploded = HashSet::<(isize, isize)>::new();
-                let front: HashSet<(isize, isize)> = [(i, j)].into_iter().collect();
+pub fn solve_part_3(input: &str) -> Result<String, SolveError> {
+    let map = Grid::parse_digits(input)?;
+    let progress = Progress::with_total("barrels", 3 * (map.rows() * map.columns()) as u64);
+    let best_barrel = map
+        .points()
+        .map(|point| {
+            progress.inc();
+            (point, {
+                let exploded = HashSet::<Point<usize>>::new();
+                let front: HashSet<Point<usize>> = [point].into_iter().collect();
                 explode_from_barrel(&map, exploded, front)
             })
         })
         .max_by_key(|(_, exploded)| exploded.len())
         .unwrap();
-    let second_best_barrel = (0..=max_i)
-        .cartesian_product(0..=max_j)
-        .filter(|&(i, j)| !best_barrel.1.contains(&(i, j)))
-        .map(|(i, j)| {
-            ((i, j), {
+    trace::branch("barrel", 1, format_args!("{:?}", best_barrel.0));
+    let second_best_barrel = map
+        .points()
+        .filter(|point| !best_barrel.1.contains(point))
+        .map(|point| {
+            progress.inc();
+            (point, {
                 let exploded = best_barrel.1.clone();
-                let front: HashSet<(isize, isize)> = [(i, j)].into_iter().collect();
+                let front: HashSet<Point<usize>> = [point].into_iter().collect();
                 explode_from_barrel(&map, exploded, front)
             })
         })
         .max_by_key(|(_, exploded)| exploded.len())
         .unwrap();
-    let third_best_barrel = (0..=max_i)
-        .cartesian_product(0..=max_j)
-        .filter(|&(i, j)| !second_best_barrel.1.contains(&(i, j)))
-        .map(|(i, j)| {
-            ((i, j), {
+    trace::branch("barrel", 2, format_args!("{:?}", second_best_barrel.0));
+    let third_best_barrel = map
+        .points()
+        .filter(|point| !second_best_barrel.1.contains(point))
+        .map(|point| {
+            progress.inc();
+            (point, {
                 let exploded = second_best_barrel.1.clone();
-                let front: HashSet<(isize, isize)> = [(i, j)].into_iter().collect();
+                let front: HashSet<Point<usize>> = [point].into_iter().collect();
                 explode_from_barrel(&map, exploded, front)
             })
         })
         .max_by_key(|(_, exploded)| exploded.len())
         .unwrap();
-    let exploded = HashSet::<(isize, isize)>::new();
-    let front: HashSet<(isize, isize)> = [best_barrel.0, second_best_barrel.0, third_best_barrel.0]
+    trace::branch("barrel", 3, format_args!("{:?}", third_best_barrel.0));
+    let exploded = HashSet::<Point<usize>>::new();
+    let front: HashSet<Point<usize>> = [best_barrel.0, second_best_barrel.0, third_best_barrel.0]
         .into_iter()
         .collect();
-    explode_from_barrel(&map, exploded, front).len().to_string()
+    progress.finish();
+    Ok(explode_from_barrel(&map, exploded, front).len().to_string())
 }
 
 #[cfg(test)]
@@ -130,6 +109,7 @@ mod tests {
 746543
 766789"
             )
+            .unwrap()
         );
     }
 
@@ -149,6 +129,7 @@ mod tests {
 5691219769
 5443329859"
             )
+            .unwrap()
         );
     }
 
@@ -162,6 +143,7 @@ mod tests {
 5235
 3112"
             )
+            .unwrap()
         )
     }
 
@@ -182,6 +164,7 @@ mod tests {
 71222127839122222196
 56111126279711111517"
             )
+            .unwrap()
         );
     }
 }
@@ -1,3 +1,7 @@
+use crate::solver::Param;
+use crate::solver::Params;
+use crate::solver::SolveError;
+
 pub fn solve_part_1(input: &str) -> String {
     let numbers = input
         .lines()
@@ -67,8 +71,10 @@ fn solve_part_2_with_turns(input: &str, turns: i64) -> String {
     .to_string()
 }
 
-pub fn solve_part_2(input: &str) -> String {
-    solve_part_2_with_turns(input, 20252025)
+pub const TURNS: Param<i64> = Param::new("turns", "20252025");
+
+pub fn solve_part_2(input: &str, params: &Params) -> Result<String, SolveError> {
+    Ok(solve_part_2_with_turns(input, params.get(&TURNS)?))
 }
 
 pub fn solve_part_3(input: &str) -> String {
@@ -103,8 +109,14 @@ mod tests {
 12-13
 20-21
 19-23
-30-37"
+30-37",
+                &Params::default()
             )
+            .unwrap()
+        );
+        assert_eq!(
+            "1",
+            solve_part_2("10-15\n12-13", &Params::from_iter([("turns", "0")])).unwrap()
         );
     }
 
@@ -2,6 +2,21 @@ use std::collections::{HashMap, HashSet};
 
 use itertools::Itertools;
 
+use crate::trace;
+
+/// Records the barrels as a grid of `#` for active and `.` for inactive ones.
+fn trace_barrels(round: usize, barrels: &HashSet<(isize, isize)>, max_i: isize, max_j: isize) {
+    trace::grid(
+        "barrels",
+        round as u64,
+        (0..=max_i).map(|i| {
+            (0..=max_j)
+                .map(|j| if barrels.contains(&(i, j)) { '#' } else { '.' })
+                .collect::<String>()
+        }),
+    );
+}
+
 pub fn solve_part_1(input: &str) -> String {
     let mut barrels: HashSet<(isize, isize)> = input
         .lines()
@@ -16,7 +31,7 @@ pub fn solve_part_1(input: &str) -> String {
     let max_i = barrels.iter().map(|(i, _)| *i).max().unwrap();
     let max_j = barrels.iter().map(|(_, j)| *j).max().unwrap();
     let mut total_active = 0;
-    for _ in 0..10 {
+    for round in 0..10 {
         let mut next_barrels = HashSet::new();
         for i in 0..=max_i {
             for j in 0..=max_j {
@@ -36,6 +51,7 @@ pub fn solve_part_1(input: &str) -> String {
         }
         barrels = next_barrels;
         total_active += barrels.len();
+        trace_barrels(round, &barrels, max_i, max_j);
     }
     total_active.to_string()
 }
@@ -54,7 +70,7 @@ pub fn solve_part_2(input: &str) -> String {
     let max_i = barrels.iter().map(|(i, _)| *i).max().unwrap();
     let max_j = barrels.iter().map(|(_, j)| *j).max().unwrap();
     let mut total_active = 0;
-    for _ in 0..2025 {
+    for round in 0..2025 {
         let mut next_barrels = HashSet::new();
         for i in 0..=max_i {
             for j in 0..=max_j {
@@ -74,6 +90,7 @@ pub fn solve_part_2(input: &str) -> String {
         }
         barrels = next_barrels;
         total_active += barrels.len();
+        trace_barrels(round, &barrels, max_i, max_j);
     }
     total_active.to_string()
 }
@@ -1,66 +1,37 @@
-use std::{
-    cmp::Reverse,
-    collections::{HashMap, HashSet},
-    f64::consts::PI,
-};
+use crate::geometry::Point;
+use crate::grid::Grid;
+use crate::search;
+use crate::solver::SolveError;
 
-use itertools::Itertools;
-use libm::atan2;
-use priority_queue::PriorityQueue;
-
-fn l2(i: usize, j: usize) -> usize {
-    i * i + j * j
-}
-
-fn erupt(data: &[Vec<char>], vi: usize, vj: usize, r: usize) -> usize {
+/// The sum of the cells within the radius of the volcano.
+fn erupt(data: &Grid<char>, volcano: Point<usize>, r: usize) -> usize {
     let r2 = r * r;
-    data.iter()
-        .enumerate()
-        .flat_map(|(i, l)| {
-            l.iter()
-                .enumerate()
-                .filter(move |&(j, v)| *v != '@' && l2(vi.abs_diff(i), vj.abs_diff(j)) <= r2)
-        })
-        .map(|(_, v)| (*v as u8 - b'0') as usize)
+    data.points()
+        .filter(|&point| data[point] != '@' && point.euclidean_squared(volcano) <= r2)
+        .map(|point| (data[point] as u8 - b'0') as usize)
         .sum::<usize>()
 }
 
-fn find(data: &[Vec<char>], c: char) -> (usize, usize) {
-    data.iter()
-        .enumerate()
-        .flat_map(|(i, l)| {
-            l.iter()
-                .enumerate()
-                .filter(|&(_, v)| *v == c)
-                .map(move |(j, _)| (i, j))
-        })
-        .exactly_one()
-        .unwrap()
+pub fn solve_part_1(input: &str) -> Result<String, SolveError> {
+    let data = Grid::parse(input)?;
+    let volcano = data.find_unique(&'@')?;
+    Ok(erupt(&data, volcano, 10).to_string())
 }
 
-pub fn solve_part_1(input: &str) -> String {
-    let data = input
-        .lines()
-        .map(|l| l.chars().collect::<Vec<_>>())
-        .collect::<Vec<_>>();
-    let (vi, vj) = find(&data, '@');
-    erupt(&data, vi, vj, 10).to_string()
-}
-
-pub fn solve_part_2(input: &str) -> String {
-    let data = input
-        .lines()
-        .map(|l| l.chars().collect::<Vec<_>>())
-        .collect::<Vec<_>>();
-    let (vi, vj) = find(&data, '@');
-    let h = data.len();
-    let w = data[0].len();
-    let max_r = vi.max(vj).max(h - vi - 1).max(w - vj - 1);
+pub fn solve_part_2(input: &str) -> Result<String, SolveError> {
+    let data = Grid::parse(input)?;
+    let volcano = data.find_unique(&'@')?;
+    let size = data.size();
+    let max_r = volcano
+        .x
+        .max(volcano.y)
+        .max(size.y - volcano.y - 1)
+        .max(size.x - volcano.x - 1);
     let mut last_eruption = 0;
     let mut max_eruption = 0;
     let mut max_eruption_r = 0;
     for r in 1..=max_r {
-        let eruption = erupt(&data, vi, vj, r);
+        let eruption = erupt(&data, volcano, r);
         let de = eruption - last_eruption;
         if de > max_eruption {
             max_eruption = de;
@@ -68,89 +39,56 @@ pub fn solve_part_2(input: &str) -> String {
         }
         last_eruption = eruption;
     }
-    (max_eruption_r * max_eruption).to_string()
+    Ok((max_eruption_r * max_eruption).to_string())
 }
 
-pub fn solve_part_3(input: &str) -> String {
-    let data = input
-        .lines()
-        .map(|l| l.chars().collect::<Vec<_>>())
-        .collect::<Vec<_>>();
-    let width = data[0].len();
-    let height = data.len();
-    let (vi, vj) = find(&data, '@');
-    let (si, sj) = find(&data, 'S');
-
-    let azimuth = |i: usize, j: usize| atan2(i as f64 - vi as f64, j as f64 - vj as f64);
+pub fn solve_part_3(input: &str) -> Result<String, SolveError> {
+    let data = Grid::parse(input)?;
+    let size = data.size();
+    let volcano = data.find_unique(&'@')?;
+    let start = data.find_unique(&'S')?;
 
-    let small_rot = |az1: f64, az2: f64| {
-        let d = az1 - az2;
-        if d > PI {
-            d - 2. * PI
-        } else if d < -PI {
-            d + 2. * PI
+    // A loop around the volcano crosses the column boundary just right of it, below
+    // it, once more in one direction than in the other. Rightward crossings count 1.
+    let crossing = |from: Point<usize>, to: Point<usize>| -> i8 {
+        if from.y <= volcano.y {
+            0
+        } else if (from.x, to.x) == (volcano.x, volcano.x + 1) {
+            1
+        } else if (from.x, to.x) == (volcano.x + 1, volcano.x) {
+            -1
         } else {
-            d
+            0
         }
     };
 
     let solve = |radius: usize| {
         let r2 = radius * radius;
         let time_limit = ((radius + 1) * 30) as i64;
-        let mut queue = PriorityQueue::new();
-        let mut rotations = HashMap::new();
-        rotations.insert((si, sj, false), 0f64);
-        let mut visited = HashSet::new();
-        queue.push((si, sj, false), Reverse(0));
-        while let Some(((i, j, rotated), Reverse(time))) = queue.pop() {
-            if time >= time_limit {
-                break;
-            }
-            visited.insert((i, j, rotated));
-            let az = azimuth(i, j);
-            let rotation = rotations[&(i, j, rotated)];
-            for (di, dj) in [(-1, 0), (1, 0), (0, -1), (0, 1)] {
-                let (ni, nj) = (i.wrapping_add_signed(di), j.wrapping_add_signed(dj));
-                if ni >= height || nj >= width {
-                    continue;
-                }
-                if l2(ni.abs_diff(vi), nj.abs_diff(vj)) <= r2 {
-                    continue;
-                }
-                let is_rotated = if let Some(previous_rotation) = rotations.get(&(ni, nj, false)) {
-                    let rotation = rotation + small_rot(azimuth(ni, nj), az);
-                    (rotation - previous_rotation).abs() > 6.
-                } else {
-                    false
-                };
-                if (ni, nj, is_rotated) == (si, sj, true) {
-                    return Some(time);
-                }
-                if visited.contains(&(ni, nj, is_rotated)) {
-                    continue;
-                }
-                let new_time: i64 = time + (data[ni][nj] as i8 - '0' as i8) as i64;
-                let should_update =
-                    match queue.push_increase((ni, nj, is_rotated), Reverse(new_time)) {
-                        None => true,
-                        Some(Reverse(t)) => t > new_time,
-                    };
-                if should_update {
-                    rotations.insert(
-                        (ni, nj, is_rotated),
-                        rotation + small_rot(azimuth(ni, nj), az),
-                    );
-                };
-            }
-        }
-        None
+        let path = search::dijkstra(
+            [(start, 0)],
+            |&(point, crossings)| {
+                data.neighbours_4(point)
+                    .filter(|next| next.euclidean_squared(volcano) > r2)
+                    .map(|next| {
+                        let time = if next == start {
+                            0
+                        } else {
+                            (data[next] as u8 - b'0') as i64
+                        };
+                        ((next, crossings + crossing(point, next)), time)
+                    })
+                    .filter(|&((_, crossings), _)| crossings.abs() <= 1)
+                    .collect::<Vec<_>>()
+            },
+            |&(point, crossings)| point == start && crossings != 0,
+        )?;
+        (path.cost < time_limit).then_some(path.cost)
     };
-    let (radius, time) = (1..(width.min(height) / 2))
-        .map(|radius| (radius, solve(radius)))
-        .filter(|(_, s)| s.is_some())
-        .min()
-        .unwrap();
-    (radius as i64 * time.unwrap()).to_string()
+    (1..(size.x.min(size.y) / 2))
+        .find_map(|radius| Some(radius as i64 * solve(radius)?))
+        .map(|answer| answer.to_string())
+        .ok_or_else(|| SolveError::new("no loop around the volcano is fast enough"))
 }
 
 #[cfg(test)]
@@ -185,6 +123,7 @@ mod tests {
 987342622289291613318
 971977649141188759131"
             )
+            .unwrap()
         );
     }
 
@@ -207,6 +146,7 @@ mod tests {
 6694118785585
 9568991647449"
             )
+            .unwrap()
         );
     }
 
@@ -231,6 +171,7 @@ mod tests {
 643362324542432
 463332353552464"
             )
+            .unwrap()
         );
     }
 
@@ -261,6 +202,7 @@ mod tests {
 323532222353523253542
 553545434425235223552"
             )
+            .unwrap()
         );
     }
 }
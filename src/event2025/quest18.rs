@@ -1,9 +1,16 @@
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
+use std::thread;
+use std::time::Duration;
+
 use regex::Regex;
 use z3::{
     Optimize, Params,
     ast::{Bool, Int},
 };
 
+use crate::cancel;
+
 #[derive(Default)]
 struct Plant {
     thickness: i64,
@@ -35,9 +42,9 @@ fn parse_plant_spec(input: &str) -> Plant {
 
 fn eval_plant(plants: &[Plant], number: usize, free_branches: &[i64]) -> i64 {
     let plant = &plants[number - 1];
-    if plant.free.is_some() {
+    if let Some(free) = plant.free {
         assert_eq!(1, plant.thickness);
-        assert_eq!(1, plant.free.unwrap());
+        assert_eq!(1, free);
         free_branches[number - 1]
     } else {
         let incoming = plant
@@ -87,9 +94,9 @@ pub fn solve_part_2(input: &str) -> String {
 
 fn eval_plant_z3(plants: &[Plant], number: usize, free_branches: &[Option<Bool>]) -> Int {
     let plant = &plants[number - 1];
-    if plant.free.is_some() {
+    if let Some(free) = plant.free {
         assert_eq!(1, plant.thickness);
-        assert_eq!(1, plant.free.unwrap());
+        assert_eq!(1, free);
         free_branches[number - 1]
             .as_ref()
             .unwrap()
@@ -119,10 +126,35 @@ fn maximum_achievable_brightness(plants: &[Plant]) -> i64 {
     let solver = Optimize::new();
     let mut params = Params::new();
     params.set_symbol("opt.maxsat_engine", "wmax");
+    let token = cancel::current();
+    if let Some(remaining) = token.remaining() {
+        params.set_u32(
+            "timeout",
+            remaining.as_millis().try_into().unwrap_or(u32::MAX),
+        );
+    }
     solver.set_params(&params);
     let brightness = eval_plant_z3(plants, plants.len(), &free_branches);
     solver.maximize(&brightness);
-    match solver.check(&[]) {
+    // z3 does not check the token itself, so interrupt it on Ctrl-C.
+    let done = AtomicBool::new(false);
+    let handle = solver.get_context().handle();
+    let result = thread::scope(|scope| {
+        scope.spawn(|| {
+            while !done.load(Ordering::Relaxed) {
+                if token.is_cancelled() {
+                    handle.interrupt();
+                    return;
+                }
+                thread::sleep(Duration::from_millis(50));
+            }
+        });
+        let result = solver.check(&[]);
+        done.store(true, Ordering::Relaxed);
+        result
+    });
+    cancel::checkpoint();
+    match result {
         z3::SatResult::Sat => solver
             .get_model()
             .unwrap()
@@ -130,7 +162,11 @@ fn maximum_achievable_brightness(plants: &[Plant]) -> i64 {
             .unwrap()
             .as_i64()
             .unwrap(),
-        _ => panic!("unsat"),
+        z3::SatResult::Unknown => panic!(
+            "z3 gave up: {}",
+            solver.get_reason_unknown().unwrap_or_default()
+        ),
+        z3::SatResult::Unsat => panic!("unsat"),
     }
 }
 
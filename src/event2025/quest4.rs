@@ -1,35 +1,61 @@
 use num::{BigInt, Integer};
 
-pub fn solve_part_1(input: &str) -> String {
-    let gears: Vec<i64> = input.trim().lines().map(|g| g.parse().unwrap()).collect();
-    (2025 * gears[0] / gears.last().unwrap()).to_string()
+use crate::solver::Answer;
+use crate::solver::SolveError;
+
+/// Parses a gear's number of teeth, reporting its position in the input on failure.
+fn parse_teeth(input: &str, teeth: &str) -> Result<BigInt, SolveError> {
+    BigInt::parse_bytes(teeth.as_bytes(), 10)
+        .ok_or_else(|| SolveError::at_fragment(input, teeth, format!("invalid gear {teeth:?}")))
+}
+
+fn parse_gears(input: &str) -> Result<Vec<BigInt>, SolveError> {
+    let gears = input
+        .trim()
+        .lines()
+        .map(|g| parse_teeth(input, g))
+        .collect::<Result<Vec<BigInt>, SolveError>>()?;
+    if gears.len() < 2 {
+        return Err(SolveError::new("expected at least two gears"));
+    }
+    Ok(gears)
+}
+
+pub fn solve_part_1(input: &str) -> Result<Answer, SolveError> {
+    let gears = parse_gears(input)?;
+    Ok(Answer::from(2025 * &gears[0] / gears.last().unwrap()))
 }
 
-pub fn solve_part_2(input: &str) -> String {
-    let gears: Vec<i64> = input.trim().lines().map(|g| g.parse().unwrap()).collect();
+pub fn solve_part_2(input: &str) -> Result<Answer, SolveError> {
+    let gears = parse_gears(input)?;
     let res = (BigInt::parse_bytes(b"10000000000000", 10).unwrap() * gears.last().unwrap())
-        .div_ceil(&(BigInt::ZERO + gears[0]));
-    res.to_string()
+        .div_ceil(&gears[0]);
+    Ok(Answer::from(res))
 }
 
-pub fn solve_part_3(input: &str) -> String {
+pub fn solve_part_3(input: &str) -> Result<Answer, SolveError> {
     let mut lines = input.trim().lines();
-    let first_gear = BigInt::parse_bytes(lines.next().unwrap().as_bytes(), 10).unwrap();
+    let first_gear = parse_teeth(
+        input,
+        lines
+            .next()
+            .ok_or_else(|| SolveError::new("expected at least one gear"))?,
+    )?;
     let mut nominator: BigInt = first_gear * 100;
     let mut denominator: BigInt = BigInt::ZERO + 1;
     for line in lines {
         let mut split = line.split("|");
-        denominator *= BigInt::parse_bytes(split.next().unwrap().as_bytes(), 10).unwrap();
+        denominator *= parse_teeth(input, split.next().unwrap())?;
         match split.next() {
             Some(size) => {
-                nominator *= BigInt::parse_bytes(size.as_bytes(), 10).unwrap();
+                nominator *= parse_teeth(input, size)?;
             }
             None => {
                 break;
             }
         }
     }
-    (nominator / denominator).to_string()
+    Ok(Answer::from(nominator / denominator))
 }
 
 #[cfg(test)]
@@ -49,6 +75,8 @@ mod tests {
 16
 8"
             )
+            .unwrap()
+            .to_string()
         );
         assert_eq!(
             "15888",
@@ -59,6 +87,8 @@ mod tests {
 35
 13"
             )
+            .unwrap()
+            .to_string()
         );
     }
 
@@ -73,6 +103,8 @@ mod tests {
 16
 8"
             )
+            .unwrap()
+            .to_string()
         );
         assert_eq!(
             "1274509803922",
@@ -83,7 +115,18 @@ mod tests {
 35
 13"
             )
+            .unwrap()
+            .to_string()
+        );
+    }
+
+    #[test]
+    fn test_invalid_gear() {
+        assert_eq!(
+            "line 2, col 3: invalid gear \"6x\"",
+            solve_part_3("5\n5|6x\n5").unwrap_err().to_string()
         );
+        assert!(solve_part_1("128").is_err());
     }
 
     #[test]
@@ -96,6 +139,8 @@ mod tests {
 10|20
 5"
             )
+            .unwrap()
+            .to_string()
         );
         assert_eq!(
             "6818",
@@ -108,6 +153,8 @@ mod tests {
 10|50
 11"
             )
+            .unwrap()
+            .to_string()
         );
     }
 }
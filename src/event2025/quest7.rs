@@ -1,80 +1,49 @@
 use std::collections::{HashMap, HashSet};
 
-pub fn solve_part_1(input: &str) -> String {
-    let (names, rules) = input.split_once("\n\n").unwrap();
-    let names: Vec<&str> = names.split(",").collect();
-    let rules: HashMap<char, HashSet<char>> = rules
-        .lines()
-        .map(|line| {
-            let (from, to) = line.split_once(" > ").unwrap();
-            let to = to.split(",");
-            (
-                from.chars().next().unwrap(),
-                to.map(|s| s.chars().next().unwrap()).collect(),
-            )
-        })
-        .collect();
-    for name in names {
-        let mut allowed_chars = rules.get(&name.chars().next().unwrap());
-        let mut acceptable = true;
-        for ch in name.chars().skip(1) {
+use crate::quest::Quest;
+use crate::solver::Answer;
+use crate::solver::SolveError;
+
+/// The names (or name prefixes in part 3) and which letter may follow which.
+pub struct Names {
+    names: Vec<String>,
+    rules: HashMap<char, HashSet<char>>,
+}
+
+impl Names {
+    fn is_acceptable(&self, name: &str) -> Result<bool, SolveError> {
+        let mut chars = name.chars();
+        let Some(first) = chars.next() else {
+            return Ok(false);
+        };
+        let mut allowed_chars = self.rules.get(&first);
+        for ch in chars {
             match allowed_chars {
                 Some(allowed) => {
                     if !allowed.contains(&ch) {
-                        acceptable = false;
-                        break;
+                        return Ok(false);
                     }
-                    allowed_chars = rules.get(&ch);
+                    allowed_chars = self.rules.get(&ch);
                 }
                 None => {
-                    panic!("no rules for letter {ch} in name {name}");
+                    return Err(SolveError::new(format!(
+                        "no rules for letter {ch} in name {name}"
+                    )));
                 }
             }
         }
-        if acceptable {
-            return name.to_string();
-        }
+        Ok(true)
     }
-    panic!("all names bad");
-}
 
-pub fn solve_part_2(input: &str) -> String {
-    let (names, rules) = input.split_once("\n\n").unwrap();
-    let names: Vec<&str> = names.split(",").collect();
-    let rules: HashMap<char, HashSet<char>> = rules
-        .lines()
-        .map(|line| {
-            let (from, to) = line.split_once(" > ").unwrap();
-            let to = to.split(",");
-            (
-                from.chars().next().unwrap(),
-                to.map(|s| s.chars().next().unwrap()).collect(),
-            )
-        })
-        .collect();
-    let mut sum_of_indices = 0;
-    for (i, name) in names.into_iter().enumerate() {
-        let mut allowed_chars = rules.get(&name.chars().next().unwrap());
-        let mut acceptable = true;
-        for ch in name.chars().skip(1) {
-            match allowed_chars {
-                Some(allowed) => {
-                    if !allowed.contains(&ch) {
-                        acceptable = false;
-                        break;
-                    }
-                    allowed_chars = rules.get(&ch);
-                }
-                None => {
-                    panic!("no rules for letter {ch} in name {name}");
-                }
+    fn acceptable_names(&self) -> Result<Vec<(usize, &str)>, SolveError> {
+        let mut acceptable = vec![];
+        for (i, name) in self.names.iter().enumerate() {
+            if self.is_acceptable(name)? {
+                acceptable.push((i, name.as_str()));
             }
         }
-        if acceptable {
-            sum_of_indices += 1 + i;
-        }
+        Ok(acceptable)
     }
-    sum_of_indices.to_string()
 }
 
 fn gen_names_with_prefix(
@@ -97,49 +66,70 @@ fn gen_names_with_prefix(
     }
 }
 
-pub fn solve_part_3(input: &str) -> String {
-    let (prefix, rules) = input.split_once("\n\n").unwrap();
-    let prefixes: Vec<_> = prefix.split(",").collect();
-    let rules: HashMap<char, HashSet<char>> = rules
-        .lines()
-        .map(|line| {
-            let (from, to) = line.split_once(" > ").unwrap();
-            let to = to.split(",");
-            (
-                from.chars().next().unwrap(),
-                to.map(|s| s.chars().next().unwrap()).collect(),
-            )
-        })
-        .collect();
-    let mut results: HashSet<String> = HashSet::new();
-    prefixes
-        .into_iter()
-        .filter(|&name| {
-            let mut allowed_chars = rules.get(&name.chars().next().unwrap());
-            let mut acceptable = true;
-            for ch in name.chars().skip(1) {
-                match allowed_chars {
-                    Some(allowed) => {
-                        if !allowed.contains(&ch) {
-                            acceptable = false;
-                            break;
-                        }
-                        allowed_chars = rules.get(&ch);
-                    }
-                    None => {
-                        panic!("no rules for letter {ch} in name {name}");
-                    }
-                }
-            }
-            acceptable
+/// Parses a single letter, e.g. the `r` in `r > a,i,o`.
+fn parse_letter(input: &str, letter: &str) -> Result<char, SolveError> {
+    let mut chars = letter.chars();
+    match (chars.next(), chars.next()) {
+        (Some(ch), None) => Ok(ch),
+        _ => Err(SolveError::at_fragment(
+            input,
+            letter,
+            format!("expected a single letter, found {letter:?}"),
+        )),
+    }
+}
+
+impl Quest for Names {
+    type Model = Names;
+
+    fn parse(input: &str) -> Result<Names, SolveError> {
+        let (names, rules) = input.split_once("\n\n").ok_or_else(|| {
+            SolveError::new("expected the names and the rules separated by a blank line")
+        })?;
+        let mut parsed_rules = HashMap::new();
+        for line in rules.lines() {
+            let (from, to) = line.split_once(" > ").ok_or_else(|| {
+                SolveError::at_fragment(input, line, "expected a rule like r > a,i,o")
+            })?;
+            let to = to
+                .split(",")
+                .map(|letter| parse_letter(input, letter))
+                .collect::<Result<HashSet<char>, SolveError>>()?;
+            parsed_rules.insert(parse_letter(input, from)?, to);
+        }
+        Ok(Names {
+            names: names.split(",").map(|s| s.to_string()).collect(),
+            rules: parsed_rules,
         })
-        .for_each(|prefix| gen_names_with_prefix(prefix, &rules, &mut results));
-    results.len().to_string()
+    }
+
+    fn part_1(model: &Names) -> Result<Answer, SolveError> {
+        model
+            .acceptable_names()?
+            .first()
+            .map(|&(_, name)| Answer::from(name))
+            .ok_or_else(|| SolveError::new("all names bad"))
+    }
+
+    fn part_2(model: &Names) -> Result<Answer, SolveError> {
+        let sum_of_indices: usize = model.acceptable_names()?.iter().map(|(i, _)| i + 1).sum();
+        Ok(Answer::from(sum_of_indices))
+    }
+
+    fn part_3(model: &Names) -> Result<Answer, SolveError> {
+        let mut results: HashSet<String> = HashSet::new();
+        for (_, prefix) in model.acceptable_names()? {
+            gen_names_with_prefix(prefix, &model.rules, &mut results);
+        }
+        Ok(Answer::from(results.len()))
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::quest::solve;
+    use crate::types::Part;
 
     use test_log::test;
 
@@ -147,7 +137,8 @@ mod tests {
     fn test_solve_part_1() {
         assert_eq!(
             "Oroneth",
-            solve_part_1(
+            solve::<Names>(
+                Part::One,
                 "Oronris,Urakris,Oroneth,Uraketh
 
 r > a,i,o
@@ -161,6 +152,8 @@ e > t
 O > r
 t > h"
             )
+            .unwrap()
+            .to_string()
         )
     }
 
@@ -168,7 +161,8 @@ t > h"
     fn test_solve_part_2() {
         assert_eq!(
             "23",
-            solve_part_2(
+            solve::<Names>(
+                Part::Two,
                 "Xanverax,Khargyth,Nexzeth,Helther,Braerex,Tirgryph,Kharverax
 
 r > v,e,a,g,y
@@ -192,6 +186,8 @@ n > v
 x > z
 T > i"
             )
+            .unwrap()
+            .to_string()
         );
     }
 
@@ -199,7 +195,8 @@ T > i"
     fn test_solve_part_3() {
         assert_eq!(
             "25",
-            solve_part_3(
+            solve::<Names>(
+                Part::Three,
                 "Xaryt
 
 X > a,o
@@ -210,10 +207,13 @@ t > h
 v > e
 y > p,t"
             )
+            .unwrap()
+            .to_string()
         );
         assert_eq!(
             "1154",
-            solve_part_3(
+            solve::<Names>(
+                Part::Three,
                 "Khara,Xaryt,Noxer,Kharax
 
 r > v,e,a,g,y
@@ -237,6 +237,8 @@ n > v
 x > z
 T > i"
             )
+            .unwrap()
+            .to_string()
         );
     }
 }
@@ -1,16 +1,19 @@
-pub fn solve_part_1(input: &str) -> String {
-    let numbers: Vec<i32> = input.split(",").map(|x| x.parse().unwrap()).collect();
+use crate::parse::Input;
+use crate::solver::SolveError;
+
+pub fn solve_part_1(input: &str) -> Result<String, SolveError> {
+    let numbers: Vec<i32> = Input::new(input).comma_list(input.trim_end())?;
     let mut count = 0;
     for i in 1..numbers.len() {
         if numbers[i].abs_diff(numbers[i - 1]) == 16 {
             count += 1;
         }
     }
-    count.to_string()
+    Ok(count.to_string())
 }
 
-pub fn solve_part_2(input: &str) -> String {
-    let numbers: Vec<i32> = input.split(",").map(|x| x.parse().unwrap()).collect();
+pub fn solve_part_2(input: &str) -> Result<String, SolveError> {
+    let numbers: Vec<i32> = Input::new(input).comma_list(input.trim_end())?;
     let mut lines: Vec<(i32, i32)> = vec![];
     for i in 1..numbers.len() {
         let (a, b) = (numbers[i - 1], numbers[i]);
@@ -35,11 +38,11 @@ pub fn solve_part_2(input: &str) -> String {
             }
         }
     }
-    knots.to_string()
+    Ok(knots.to_string())
 }
 
-pub fn solve_part_3(input: &str) -> String {
-    let numbers: Vec<i32> = input.split(",").map(|x| x.parse().unwrap()).collect();
+pub fn solve_part_3(input: &str) -> Result<String, SolveError> {
+    let numbers: Vec<i32> = Input::new(input).comma_list(input.trim_end())?;
     let mut lines: Vec<(i32, i32)> = vec![];
     for i in 1..numbers.len() {
         let (a, b) = (numbers[i - 1], numbers[i]);
@@ -71,5 +74,5 @@ pub fn solve_part_3(input: &str) -> String {
             }
         }
     }
-    best_cut_threads.to_string()
+    Ok(best_cut_threads.to_string())
 }
@@ -0,0 +1,361 @@
+use std::error;
+use std::fmt;
+use std::ops::Add;
+use std::ops::Mul;
+use std::ops::Sub;
+use std::str::FromStr;
+
+/// A point in the plane, or the offset between two points. `x` grows to the right and
+/// `y` downwards, as in the puzzle maps: `y` is the row and `x` the column.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct Point<T> {
+    pub x: T,
+    pub y: T,
+}
+
+impl<T> Point<T> {
+    pub const fn new(x: T, y: T) -> Point<T> {
+        Point { x, y }
+    }
+}
+
+impl<T: Add<Output = T>> Add for Point<T> {
+    type Output = Point<T>;
+
+    fn add(self, other: Point<T>) -> Point<T> {
+        Point::new(self.x + other.x, self.y + other.y)
+    }
+}
+
+impl<T: Sub<Output = T>> Sub for Point<T> {
+    type Output = Point<T>;
+
+    fn sub(self, other: Point<T>) -> Point<T> {
+        Point::new(self.x - other.x, self.y - other.y)
+    }
+}
+
+impl<T: Copy + Mul<Output = T>> Mul<T> for Point<T> {
+    type Output = Point<T>;
+
+    fn mul(self, factor: T) -> Point<T> {
+        Point::new(self.x * factor, self.y * factor)
+    }
+}
+
+/// The distance between two numbers, which works for unsigned ones too.
+fn abs_diff<T: Copy + Ord + Sub<Output = T>>(a: T, b: T) -> T {
+    if a > b { a - b } else { b - a }
+}
+
+impl<T> Point<T>
+where
+    T: Copy + Ord + Add<Output = T> + Sub<Output = T> + Mul<Output = T>,
+{
+    /// The number of steps between the points when moving along the axes.
+    pub fn manhattan(self, other: Point<T>) -> T {
+        abs_diff(self.x, other.x) + abs_diff(self.y, other.y)
+    }
+
+    /// The number of steps between the points when moving diagonally too.
+    pub fn chebyshev(self, other: Point<T>) -> T {
+        abs_diff(self.x, other.x).max(abs_diff(self.y, other.y))
+    }
+
+    /// The square of the straight line distance, which is exact for integers.
+    pub fn euclidean_squared(self, other: Point<T>) -> T {
+        let (dx, dy) = (abs_diff(self.x, other.x), abs_diff(self.y, other.y));
+        dx * dx + dy * dy
+    }
+}
+
+impl Point<usize> {
+    /// The point moved by the offset, unless that makes a coordinate negative.
+    pub fn checked_step(self, offset: impl Into<Point<isize>>) -> Option<Point<usize>> {
+        let offset = offset.into();
+        Some(Point::new(
+            self.x.checked_add_signed(offset.x)?,
+            self.y.checked_add_signed(offset.y)?,
+        ))
+    }
+
+    /// The point moved by the offset, if it is still inside the area of the given size
+    /// with its top left corner at the origin.
+    pub fn step_within(
+        self,
+        offset: impl Into<Point<isize>>,
+        size: Point<usize>,
+    ) -> Option<Point<usize>> {
+        self.checked_step(offset)
+            .filter(|point| point.x < size.x && point.y < size.y)
+    }
+}
+
+impl<T> From<(T, T)> for Point<T> {
+    fn from((x, y): (T, T)) -> Point<T> {
+        Point::new(x, y)
+    }
+}
+
+impl<T> From<Point<T>> for (T, T) {
+    fn from(point: Point<T>) -> (T, T) {
+        (point.x, point.y)
+    }
+}
+
+/// The moves of a chess knight.
+pub const KNIGHT_MOVES: [Point<isize>; 8] = [
+    Point::new(1, 2),
+    Point::new(-1, 2),
+    Point::new(-1, -2),
+    Point::new(1, -2),
+    Point::new(2, 1),
+    Point::new(-2, 1),
+    Point::new(-2, -1),
+    Point::new(2, -1),
+];
+
+/// A direction which is not one of the known letters.
+#[derive(Debug, PartialEq)]
+pub struct InvalidDirection(pub String);
+
+impl fmt::Display for InvalidDirection {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "invalid direction {:?}, expected one of U, D, L, R, N, E, S, W",
+            self.0
+        )
+    }
+}
+
+impl error::Error for InvalidDirection {}
+
+/// One of the four directions along the axes, in clockwise order.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum Dir4 {
+    Up,
+    Right,
+    Down,
+    Left,
+}
+
+impl Dir4 {
+    pub const ALL: [Dir4; 4] = [Dir4::Up, Dir4::Right, Dir4::Down, Dir4::Left];
+
+    fn from_index(i: usize) -> Dir4 {
+        Dir4::ALL[i % 4]
+    }
+
+    pub fn turn_right(self) -> Dir4 {
+        Dir4::from_index(self as usize + 1)
+    }
+
+    pub fn turn_left(self) -> Dir4 {
+        Dir4::from_index(self as usize + 3)
+    }
+
+    pub fn reverse(self) -> Dir4 {
+        Dir4::from_index(self as usize + 2)
+    }
+
+    /// The offset of one step in the direction.
+    pub fn delta<T: From<i8>>(self) -> Point<T> {
+        let (x, y) = match self {
+            Dir4::Up => (0, -1),
+            Dir4::Right => (1, 0),
+            Dir4::Down => (0, 1),
+            Dir4::Left => (-1, 0),
+        };
+        Point::new(T::from(x), T::from(y))
+    }
+}
+
+impl<T: From<i8>> From<Dir4> for Point<T> {
+    fn from(dir: Dir4) -> Point<T> {
+        dir.delta()
+    }
+}
+
+impl TryFrom<char> for Dir4 {
+    type Error = InvalidDirection;
+
+    /// Parses `U`, `R`, `D` and `L`, or the compass letters `N`, `E`, `S` and `W`.
+    fn try_from(c: char) -> Result<Dir4, InvalidDirection> {
+        match c {
+            'U' | 'N' => Ok(Dir4::Up),
+            'R' | 'E' => Ok(Dir4::Right),
+            'D' | 'S' => Ok(Dir4::Down),
+            'L' | 'W' => Ok(Dir4::Left),
+            _ => Err(InvalidDirection(c.to_string())),
+        }
+    }
+}
+
+impl FromStr for Dir4 {
+    type Err = InvalidDirection;
+
+    fn from_str(s: &str) -> Result<Dir4, InvalidDirection> {
+        let mut chars = s.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => Dir4::try_from(c),
+            _ => Err(InvalidDirection(s.to_string())),
+        }
+    }
+}
+
+/// One of the eight directions along the axes and the diagonals, in clockwise order.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum Dir8 {
+    N,
+    NE,
+    E,
+    SE,
+    S,
+    SW,
+    W,
+    NW,
+}
+
+impl Dir8 {
+    pub const ALL: [Dir8; 8] = [
+        Dir8::N,
+        Dir8::NE,
+        Dir8::E,
+        Dir8::SE,
+        Dir8::S,
+        Dir8::SW,
+        Dir8::W,
+        Dir8::NW,
+    ];
+
+    fn from_index(i: usize) -> Dir8 {
+        Dir8::ALL[i % 8]
+    }
+
+    /// Turns by 45 degrees clockwise.
+    pub fn turn_right(self) -> Dir8 {
+        Dir8::from_index(self as usize + 1)
+    }
+
+    /// Turns by 45 degrees counterclockwise.
+    pub fn turn_left(self) -> Dir8 {
+        Dir8::from_index(self as usize + 7)
+    }
+
+    pub fn reverse(self) -> Dir8 {
+        Dir8::from_index(self as usize + 4)
+    }
+
+    /// The offset of one step in the direction.
+    pub fn delta<T: From<i8>>(self) -> Point<T> {
+        let (x, y) = match self {
+            Dir8::N => (0, -1),
+            Dir8::NE => (1, -1),
+            Dir8::E => (1, 0),
+            Dir8::SE => (1, 1),
+            Dir8::S => (0, 1),
+            Dir8::SW => (-1, 1),
+            Dir8::W => (-1, 0),
+            Dir8::NW => (-1, -1),
+        };
+        Point::new(T::from(x), T::from(y))
+    }
+}
+
+impl From<Dir4> for Dir8 {
+    fn from(dir: Dir4) -> Dir8 {
+        Dir8::from_index(dir as usize * 2)
+    }
+}
+
+impl<T: From<i8>> From<Dir8> for Point<T> {
+    fn from(dir: Dir8) -> Point<T> {
+        dir.delta()
+    }
+}
+
+impl FromStr for Dir8 {
+    type Err = InvalidDirection;
+
+    /// Parses the compass points such as `N` or `SE`, or `U`, `R`, `D` and `L`.
+    fn from_str(s: &str) -> Result<Dir8, InvalidDirection> {
+        match s {
+            "NE" => Ok(Dir8::NE),
+            "SE" => Ok(Dir8::SE),
+            "SW" => Ok(Dir8::SW),
+            "NW" => Ok(Dir8::NW),
+            _ => s.parse::<Dir4>().map(Dir8::from),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_distances() {
+        let (a, b) = (Point::new(1i64, 5), Point::new(4, 1));
+        assert_eq!(7, a.manhattan(b));
+        assert_eq!(4, a.chebyshev(b));
+        assert_eq!(25, a.euclidean_squared(b));
+        assert_eq!(7, Point::new(1usize, 5).manhattan(Point::new(4, 1)));
+        assert_eq!(Point::new(5, 6), a + b);
+        assert_eq!(Point::new(-3, 4), a - b);
+        assert_eq!(Point::new(2, 10), a * 2);
+    }
+
+    #[test]
+    fn test_turns() {
+        assert_eq!(Dir4::Left, Dir4::Up.turn_left());
+        assert_eq!(Dir4::Up, Dir4::Left.turn_right());
+        assert_eq!(Dir4::Down, Dir4::Up.reverse());
+        assert_eq!(Dir8::NW, Dir8::N.turn_left());
+        assert_eq!(Dir8::SW, Dir8::NE.reverse());
+        assert_eq!(Dir8::W, Dir8::from(Dir4::Left));
+        for dir in Dir4::ALL {
+            assert_eq!(dir, dir.turn_left().turn_right());
+            assert_eq!(
+                Point::<i32>::default(),
+                dir.delta::<i32>() + dir.reverse().delta()
+            );
+            assert_eq!(dir.delta::<i32>(), Dir8::from(dir).delta());
+        }
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(Ok(Dir4::Up), "U".parse());
+        assert_eq!(Ok(Dir4::Up), "N".parse());
+        assert_eq!(Ok(Dir4::Left), Dir4::try_from('W'));
+        assert!("X".parse::<Dir4>().is_err());
+        assert!("UU".parse::<Dir4>().is_err());
+        assert_eq!(Ok(Dir8::SE), "SE".parse());
+        assert_eq!(Ok(Dir8::E), "R".parse());
+        assert_eq!(
+            "invalid direction \"X\", expected one of U, D, L, R, N, E, S, W",
+            "X".parse::<Dir8>().unwrap_err().to_string()
+        );
+    }
+
+    #[test]
+    fn test_step_within() {
+        let size = Point::new(3, 2);
+        assert_eq!(
+            Some(Point::new(1, 1)),
+            Point::new(1usize, 0).step_within(Dir4::Down, size)
+        );
+        assert_eq!(None, Point::new(0usize, 0).step_within(Dir4::Left, size));
+        assert_eq!(None, Point::new(0usize, 0).checked_step(Dir4::Up));
+        assert_eq!(
+            Some(Point::new(7, 0)),
+            Point::new(6usize, 0).checked_step(Dir4::Right)
+        );
+        assert_eq!(None, Point::new(2usize, 0).step_within(Dir8::E, size));
+        assert_eq!(
+            Some(Point::new(2, 1)),
+            Point::new(0usize, 0).step_within(KNIGHT_MOVES[4] - Point::new(0, 0), size)
+        );
+    }
+}
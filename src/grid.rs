@@ -0,0 +1,370 @@
+use std::fmt;
+use std::ops::Index;
+use std::ops::IndexMut;
+
+use crate::geometry::Dir4;
+use crate::geometry::Dir8;
+use crate::geometry::Point;
+use crate::parse::Input;
+use crate::solver::SolveError;
+
+/// A rectangular grid, indexed by points whose `x` is the column and `y` the row,
+/// counted from the top left corner.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Grid<T> {
+    rows: usize,
+    columns: usize,
+    cells: Vec<T>,
+}
+
+impl Grid<char> {
+    /// Parses a grid of characters, one row per line.
+    pub fn parse(text: &str) -> Result<Grid<char>, SolveError> {
+        Grid::parse_with(text, |_, c| Ok(c))
+    }
+}
+
+impl Grid<u8> {
+    /// Parses a grid of single digits, one row per line.
+    pub fn parse_digits(text: &str) -> Result<Grid<u8>, SolveError> {
+        let input = Input::new(text);
+        Grid::from_rows(input.digit_grid(text)?)
+            .map_err(|row| input.error(text.lines().nth(row).unwrap(), "ragged row"))
+    }
+}
+
+impl<T> Grid<T> {
+    /// A grid made of the rows, or the index of the first row whose length differs
+    /// from the first one.
+    pub fn from_rows(rows: Vec<Vec<T>>) -> Result<Grid<T>, usize> {
+        let columns = rows.first().map_or(0, |row| row.len());
+        if let Some(ragged) = rows.iter().position(|row| row.len() != columns) {
+            return Err(ragged);
+        }
+        Ok(Grid {
+            rows: rows.len(),
+            columns,
+            cells: rows.into_iter().flatten().collect(),
+        })
+    }
+
+    /// Parses a grid one row per line, converting each character with `cell`, which
+    /// is given the slice of the input it came from for error reporting.
+    pub fn parse_with(
+        text: &str,
+        mut cell: impl FnMut(&str, char) -> Result<T, SolveError>,
+    ) -> Result<Grid<T>, SolveError> {
+        let input = Input::new(text);
+        let mut rows = vec![];
+        for line in input.lines() {
+            let row: Vec<T> = line
+                .char_indices()
+                .map(|(j, c)| cell(&line[j..], c))
+                .collect::<Result<_, _>>()?;
+            if let Some(first) = rows.first().map(Vec::len)
+                && row.len() != first
+            {
+                return Err(input.error(
+                    line,
+                    format!("expected {first} columns, found {}", row.len()),
+                ));
+            }
+            rows.push(row);
+        }
+        Ok(Grid::from_rows(rows).expect("rows have the same length"))
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn columns(&self) -> usize {
+        self.columns
+    }
+
+    pub fn row(&self, i: usize) -> &[T] {
+        &self.cells[i * self.columns..(i + 1) * self.columns]
+    }
+
+    /// The number of columns and rows, as the point just outside the bottom right corner.
+    pub fn size(&self) -> Point<usize> {
+        Point::new(self.columns, self.rows)
+    }
+
+    /// The points of all cells, row by row.
+    pub fn points(&self) -> impl Iterator<Item = Point<usize>> + use<T> {
+        let columns = self.columns;
+        (0..self.rows * self.columns).map(move |n| Point::new(n % columns, n / columns))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (Point<usize>, &T)> {
+        self.points().zip(self.cells.iter())
+    }
+
+    /// The point moved by the offset, if it is still inside the grid.
+    pub fn step(
+        &self,
+        point: Point<usize>,
+        offset: impl Into<Point<isize>>,
+    ) -> Option<Point<usize>> {
+        point.step_within(offset, self.size())
+    }
+
+    /// The points inside the grid sharing an edge with the point, clockwise from the
+    /// one above.
+    pub fn neighbours_4(&self, point: Point<usize>) -> impl Iterator<Item = Point<usize>> {
+        Dir4::ALL
+            .into_iter()
+            .filter_map(move |dir| self.step(point, dir))
+    }
+
+    /// The points inside the grid sharing an edge or a corner with the point,
+    /// clockwise from the one above.
+    pub fn neighbours_8(&self, point: Point<usize>) -> impl Iterator<Item = Point<usize>> {
+        Dir8::ALL
+            .into_iter()
+            .filter_map(move |dir| self.step(point, dir))
+    }
+
+    /// The points of the cells equal to the value.
+    pub fn find_all<'a>(&'a self, value: &'a T) -> impl Iterator<Item = Point<usize>> + 'a
+    where
+        T: PartialEq,
+    {
+        self.iter()
+            .filter(move |&(_, cell)| cell == value)
+            .map(|(point, _)| point)
+    }
+
+    /// The point of the first cell equal to the value, e.g. a start marker.
+    pub fn find(&self, value: &T) -> Option<Point<usize>>
+    where
+        T: PartialEq,
+    {
+        self.find_all(value).next()
+    }
+
+    /// The point of the only cell equal to the value, for markers which must appear
+    /// exactly once.
+    pub fn find_unique(&self, value: &T) -> Result<Point<usize>, SolveError>
+    where
+        T: PartialEq + fmt::Debug,
+    {
+        let mut found = self.find_all(value);
+        match (found.next(), found.next()) {
+            (Some(point), None) => Ok(point),
+            (None, _) => Err(SolveError::new(format!("no {value:?} in the grid"))),
+            (Some(_), Some(_)) => Err(SolveError::new(format!(
+                "more than one {value:?} in the grid"
+            ))),
+        }
+    }
+
+    pub fn map<U>(&self, f: impl FnMut(&T) -> U) -> Grid<U> {
+        Grid {
+            rows: self.rows,
+            columns: self.columns,
+            cells: self.cells.iter().map(f).collect(),
+        }
+    }
+
+    /// The part of the grid of the given size whose top left corner is at `start`,
+    /// which can be changed.
+    pub fn view_mut(&mut self, start: Point<usize>, size: Point<usize>) -> GridViewMut<'_, T> {
+        let end = start + size;
+        if end.x > self.columns || end.y > self.rows {
+            panic!(
+                "view of size {size:?} at {start:?} exceeds the grid size {:?}",
+                self.size()
+            );
+        }
+        GridViewMut {
+            grid: self,
+            start,
+            size,
+        }
+    }
+}
+
+impl<T> Index<Point<usize>> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, point: Point<usize>) -> &T {
+        if point.x >= self.columns || point.y >= self.rows {
+            panic!("Grid[] with {point:?} exceeds the size {:?}", self.size());
+        }
+        &self.cells[point.y * self.columns + point.x]
+    }
+}
+
+impl<T> IndexMut<Point<usize>> for Grid<T> {
+    fn index_mut(&mut self, point: Point<usize>) -> &mut T {
+        if point.x >= self.columns || point.y >= self.rows {
+            panic!("Grid[] with {point:?} exceeds the size {:?}", self.size());
+        }
+        &mut self.cells[point.y * self.columns + point.x]
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for Grid<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for i in 0..self.rows {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            for cell in self.row(i) {
+                write!(f, "{cell}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A rectangular part of a grid which can be changed, indexed from its own top left
+/// corner.
+pub struct GridViewMut<'a, T> {
+    grid: &'a mut Grid<T>,
+    start: Point<usize>,
+    size: Point<usize>,
+}
+
+impl<T> GridViewMut<'_, T> {
+    /// Maps a point in the view to the point in the grid.
+    fn grid_point(&self, point: Point<usize>) -> Point<usize> {
+        if point.x >= self.size.x || point.y >= self.size.y {
+            panic!("view[] with {point:?} exceeds the size {:?}", self.size);
+        }
+        self.start + point
+    }
+}
+
+impl<T> Index<Point<usize>> for GridViewMut<'_, T> {
+    type Output = T;
+
+    fn index(&self, point: Point<usize>) -> &T {
+        &self.grid[self.grid_point(point)]
+    }
+}
+
+impl<T> IndexMut<Point<usize>> for GridViewMut<'_, T> {
+    fn index_mut(&mut self, point: Point<usize>) -> &mut T {
+        let point = self.grid_point(point);
+        &mut self.grid[point]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let grid = Grid::parse("ab\ncd").unwrap();
+        assert_eq!(Point::new(2, 2), grid.size());
+        assert_eq!('c', grid[Point::new(0, 1)]);
+        assert_eq!("ab\ncd", grid.to_string());
+        assert_eq!(
+            "line 2, col 1: expected 2 columns, found 3",
+            Grid::parse("ab\ncde").unwrap_err().to_string()
+        );
+        assert_eq!(
+            "line 1, col 2: expected digit, found 'x'",
+            Grid::parse_digits("1x\n23").unwrap_err().to_string()
+        );
+        assert_eq!(3, Grid::parse_digits("12\n34").unwrap()[Point::new(0, 1)]);
+    }
+
+    #[test]
+    fn test_points() {
+        let mut grid = Grid::parse("abc\ndef").unwrap();
+        assert_eq!(Point::new(3, 2), grid.size());
+        assert_eq!('f', grid[Point::new(2, 1)]);
+        grid[Point::new(1, 0)] = 'x';
+        assert_eq!("axc\ndef", grid.to_string());
+        assert_eq!(
+            vec![Point::new(0, 0), Point::new(1, 0), Point::new(2, 0)],
+            grid.points().take(3).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            Some(Point::new(2, 1)),
+            grid.step(Point::new(2, 0), Dir4::Down)
+        );
+        assert_eq!(None, grid.step(Point::new(2, 0), Dir4::Right));
+        assert_eq!(None, grid.step(Point::new(2, 0), Dir4::Up));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_bounds() {
+        let grid = Grid::parse("abc\ndef").unwrap();
+        let _ = grid[Point::new(0, 2)];
+    }
+
+    #[test]
+    fn test_neighbours() {
+        let grid = Grid::parse("...\n...\n...").unwrap();
+        assert_eq!(
+            vec![Point::new(1, 0), Point::new(0, 1)],
+            grid.neighbours_4(Point::new(0, 0)).collect::<Vec<_>>()
+        );
+        assert_eq!(4, grid.neighbours_4(Point::new(1, 1)).count());
+        assert_eq!(3, grid.neighbours_8(Point::new(2, 2)).count());
+        assert_eq!(
+            vec![
+                Point::new(1, 0),
+                Point::new(2, 0),
+                Point::new(2, 1),
+                Point::new(2, 2),
+                Point::new(1, 2),
+                Point::new(0, 2),
+                Point::new(0, 1),
+                Point::new(0, 0)
+            ],
+            grid.neighbours_8(Point::new(1, 1)).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_find() {
+        let grid = Grid::parse("S.#\n#.E\n..#").unwrap();
+        assert_eq!(Some(Point::new(0, 0)), grid.find(&'S'));
+        assert_eq!(Some(Point::new(2, 1)), grid.find(&'E'));
+        assert_eq!(None, grid.find(&'X'));
+        assert_eq!(
+            vec![Point::new(2, 0), Point::new(0, 1), Point::new(2, 2)],
+            grid.find_all(&'#').collect::<Vec<_>>()
+        );
+        assert_eq!(Ok(Point::new(2, 1)), grid.find_unique(&'E'));
+        assert_eq!(
+            "no 'X' in the grid",
+            grid.find_unique(&'X').unwrap_err().to_string()
+        );
+        assert_eq!(
+            "more than one '#' in the grid",
+            grid.find_unique(&'#').unwrap_err().to_string()
+        );
+    }
+
+    #[test]
+    fn test_map() {
+        let grid = Grid::parse("abc\ndef").unwrap();
+        assert_eq!("bcd\nefg", grid.map(|&c| (c as u8 + 1) as char).to_string());
+    }
+
+    #[test]
+    fn test_views() {
+        let mut grid = Grid::parse("abc\ndef\nghi").unwrap();
+        let mut view = grid.view_mut(Point::new(1, 0), Point::new(2, 2));
+        assert_eq!('e', view[Point::new(0, 1)]);
+        view[Point::new(0, 1)] = 'x';
+        assert_eq!("abc\ndxf\nghi", grid.to_string());
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_view_bounds() {
+        let mut grid = Grid::parse("abc\ndef\nghi").unwrap();
+        let view = grid.view_mut(Point::new(1, 1), Point::new(2, 2));
+        let _ = view[Point::new(0, 2)];
+    }
+}
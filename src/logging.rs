@@ -0,0 +1,122 @@
+use std::env;
+use std::sync::LazyLock;
+use std::sync::RwLock;
+
+use log::Log;
+use log::Metadata;
+use log::Record;
+use regex::Regex;
+
+const REDACTED: &str = "[redacted]";
+
+/// Matches the value of the EC session cookie wherever it appears in a log line, e.g.
+/// in a `Cookie:` header dumped by reqwest's verbose connection logging.
+static COOKIE_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"everybody-codes=[^;,\s\\]+").unwrap());
+
+/// Secret values which must never appear in the logs verbatim.
+static SECRETS: RwLock<Vec<String>> = RwLock::new(Vec::new());
+
+/// Registers a value that will be scrubbed from every log line emitted from now on.
+pub fn register_secret(secret: &str) {
+    if secret.is_empty() {
+        return;
+    }
+    let mut secrets = SECRETS.write().unwrap();
+    if !secrets.iter().any(|s| s == secret) {
+        secrets.push(secret.to_string());
+    }
+}
+
+/// Removes session cookies and registered secrets from the string.
+pub fn redact(line: &str) -> String {
+    let mut line = COOKIE_RE
+        .replace_all(line, format!("everybody-codes={REDACTED}"))
+        .into_owned();
+    for secret in SECRETS.read().unwrap().iter() {
+        line = line.replace(secret.as_str(), REDACTED);
+    }
+    line
+}
+
+/// A logger that scrubs secrets from every record before handing it to the wrapped
+/// logger.
+struct RedactingLogger {
+    inner: Box<dyn Log>,
+}
+
+impl Log for RedactingLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        self.inner.enabled(metadata)
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.inner.enabled(record.metadata()) {
+            return;
+        }
+        let message = redact(record.args().to_string().as_str());
+        self.inner.log(
+            &Record::builder()
+                .args(format_args!("{message}"))
+                .metadata(record.metadata().clone())
+                .module_path(record.module_path())
+                .file(record.file())
+                .line(record.line())
+                .build(),
+        );
+    }
+
+    fn flush(&self) {
+        self.inner.flush()
+    }
+}
+
+/// Initialises pretty_env_logger (configured by RUST_LOG as usual), wrapped so that
+/// no secrets can leak into the output.
+pub fn init() {
+    let mut builder = pretty_env_logger::formatted_builder();
+    if let Ok(filters) = env::var("RUST_LOG") {
+        builder.parse_filters(filters.as_str());
+    }
+    let logger = builder.build();
+    let max_level = logger.filter();
+    log::set_boxed_logger(Box::new(RedactingLogger {
+        inner: Box::new(logger),
+    }))
+    .expect("installing the logger");
+    log::set_max_level(max_level);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_redact_cookie_header() {
+        assert_eq!(
+            "cookie: everybody-codes=[redacted]; other=1",
+            redact("cookie: everybody-codes=deadbeef; other=1")
+        );
+        assert_eq!(
+            r#"write: b"cookie: everybody-codes=[redacted]\r\nhost: ec\r\n""#,
+            redact(r#"write: b"cookie: everybody-codes=deadbeef\r\nhost: ec\r\n""#)
+        );
+    }
+
+    #[test]
+    fn test_redact_registered_secret() {
+        register_secret("s3cr3t-cookie-value");
+        assert_eq!(
+            "got [redacted] from env",
+            redact("got s3cr3t-cookie-value from env")
+        );
+    }
+
+    #[test]
+    fn test_redact_leaves_other_lines_alone() {
+        assert_eq!(
+            "getting puzzle input from: https://everybody.codes/assets/2024/5/input/7.json",
+            redact("getting puzzle input from: https://everybody.codes/assets/2024/5/input/7.json")
+        );
+    }
+}
@@ -1,33 +1,232 @@
 mod autosubmit;
+mod cancel;
+mod compress;
 mod ecclient;
 mod event1;
 mod event2;
 mod event2024;
 mod event2025;
+mod geometry;
+mod grid;
+mod logging;
+mod parse;
+mod progress;
+mod quest;
+mod scaffold;
+mod search;
+mod solver;
+mod space;
+mod stats;
+mod status;
+mod submissionstore;
+mod team;
+mod trace;
+mod trigrid;
 mod types;
 mod util;
 
+use std::collections::HashSet;
 use std::env;
-use std::thread;
+use std::fs;
+use std::path::PathBuf;
+use std::time::Duration;
+use std::time::Instant;
 
-use autosubmit::submit_with_cache;
+use autosubmit::SubmissionResult;
+use autosubmit::submit_candidates;
+use clap::CommandFactory;
 use clap::Parser;
+use clap::Subcommand;
 use pretty_duration::pretty_duration;
+use quest::QuestSolver;
+use solver::FnSolver;
+use solver::ParamSolver;
+use solver::Params;
+use solver::Solver;
+use types::EventKind;
 use types::Part;
 use types::PuzzleKey;
+use types::Selector;
 
 #[derive(Parser)]
-#[command(version, about, long_about = None)]
+#[command(
+    version,
+    about,
+    long_about = None,
+    subcommand_negates_reqs = true,
+    args_conflicts_with_subcommands = true
+)]
 struct Args {
+    #[command(subcommand)]
+    command: Option<Command>,
+
     #[arg(short, long)]
     submit: bool,
 
     #[arg(short, long)]
     cookie: Option<String>,
 
-    event: i16,
-    quest: i8,
-    part: i8,
+    /// Where to keep the submission log. Files ending in .json are stored as JSON,
+    /// anything else as TOML. Use :memory: to not keep the log at all.
+    #[arg(long, default_value = "results.toml")]
+    results: PathBuf,
+
+    /// Submit even if the local sanity checks reject the answer.
+    #[arg(long)]
+    force: bool,
+
+    /// Take the session cookie from EC_COOKIE_<USER> instead of EC_COOKIE, to switch
+    /// between several accounts sharing this directory.
+    #[arg(short, long)]
+    user: Option<String>,
+
+    /// Log raw HTTP traffic at trace level (the session cookie is still redacted).
+    #[arg(long)]
+    verbose_http: bool,
+
+    /// Override a solver parameter, e.g. --param turns=3 to run an example which uses
+    /// other constants than the puzzle. May be repeated.
+    #[arg(long = "param", global = true, value_name = "NAME=VALUE", value_parser = solver::parse_param_assignment)]
+    params: Vec<(String, String)>,
+
+    /// Solve the input in this file, e.g. an example, instead of fetching the puzzle
+    /// input.
+    #[arg(long, conflicts_with = "submit")]
+    input: Option<PathBuf>,
+
+    /// How to report the progress of long running solvers.
+    #[arg(long, global = true, value_enum, default_value_t)]
+    progress: progress::Mode,
+
+    /// Give up solving after this many seconds. Ctrl-C also stops the solver cleanly.
+    #[arg(long, global = true, value_name = "SECS")]
+    timeout: Option<u64>,
+
+    /// Record the events the solvers trace, as JSON lines, to this file.
+    #[arg(long, global = true, value_name = "FILE")]
+    trace: Option<PathBuf>,
+
+    #[arg(required = true)]
+    event: Option<i16>,
+    #[arg(required = true)]
+    quest: Option<i8>,
+    #[arg(required = true)]
+    part: Option<Part>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Export statistics from the submission log. Prints Markdown to stdout unless an
+    /// output file is given.
+    Stats {
+        /// Where to keep the submission log, as for solving.
+        #[arg(long, default_value = "results.toml")]
+        results: PathBuf,
+
+        /// Only include the results of this user id.
+        #[arg(long)]
+        user_id: Option<i64>,
+
+        /// Write one row per quest part to this CSV file.
+        #[arg(long)]
+        csv: Option<PathBuf>,
+
+        /// Write the per-part and per-event tables to this Markdown file.
+        #[arg(long)]
+        markdown: Option<PathBuf>,
+
+        /// Write a bar chart of the time to solve each quest to this SVG file.
+        #[arg(long)]
+        svg: Option<PathBuf>,
+    },
+
+    /// List the puzzles which have a solver, optionally restricted by a selector such
+    /// as 2024/*/*, 2025/1-10/3 or 1/*.
+    List { selector: Option<Selector> },
+
+    /// Show which quests of each event have a solver and which are solved according to
+    /// the submission log, optionally restricted by a selector.
+    Status {
+        selector: Option<Selector>,
+
+        /// Where to keep the submission log, as for solving.
+        #[arg(long, default_value = "results.toml")]
+        results: PathBuf,
+
+        /// Only count the parts solved by this user id.
+        #[arg(long)]
+        user_id: Option<i64>,
+    },
+
+    /// Create the solver module of an event, with a placeholder for every quest which
+    /// has no solver yet. Stories get 3 quests, main events 20.
+    New {
+        event: i16,
+
+        /// The source directory of this crate.
+        #[arg(long, default_value = "src")]
+        src: PathBuf,
+    },
+
+    /// Solve every selected puzzle against the same input file, e.g. to run a whole
+    /// quest on an example. Quests which parse their input once share the model.
+    Run {
+        selector: Selector,
+
+        /// The input given to every selected solver.
+        #[arg(long)]
+        input: PathBuf,
+    },
+
+    /// Combine the submission logs of several team members into a leaderboard per
+    /// quest part. Prints Markdown to stdout unless an output file is given.
+    Team {
+        /// The members' submission logs, in TOML or JSON.
+        #[arg(required = true)]
+        results: Vec<PathBuf>,
+
+        /// Write one row per member and part to this CSV file.
+        #[arg(long)]
+        csv: Option<PathBuf>,
+
+        /// Write a table per part to this Markdown file.
+        #[arg(long)]
+        markdown: Option<PathBuf>,
+    },
+}
+
+macro_rules! try_use_param_solver {
+    ($key: expr, $params: expr, $event: pat, $quest: pat, $part: pat, $solve_fn: path, [$($param: path),*]) => {
+        if matches!(
+            *$key,
+            PuzzleKey {
+                event: $event,
+                quest: $quest,
+                part: $part,
+            }
+        ) {
+            return Some(Box::new(ParamSolver::new(
+                $solve_fn,
+                &[$($param.name),*],
+                $params.clone(),
+            )));
+        }
+    };
+}
+
+macro_rules! try_use_quest {
+    ($key: expr, $event: pat, $quest: pat, $quest_type: ty) => {
+        if matches!(
+            *$key,
+            PuzzleKey {
+                event: $event,
+                quest: $quest,
+                ..
+            }
+        ) {
+            return Some(Box::new(QuestSolver::<$quest_type>::new($key.part)));
+        }
+    };
 }
 
 macro_rules! try_use_solver {
@@ -40,12 +239,15 @@ macro_rules! try_use_solver {
                 part: $part,
             }
         ) {
-            return Box::new($solve_fn);
+            return Some(Box::new(FnSolver($solve_fn)));
         }
     };
 }
 
-fn get_solver(puzzle_key: &PuzzleKey) -> Box<dyn Fn(&str) -> String> {
+/// Events which have solvers, used to enumerate the registry.
+const EVENTS: [i16; 4] = [1, 2, 2024, 2025];
+
+fn find_solver(puzzle_key: &PuzzleKey, params: &Params) -> Option<Box<dyn Solver>> {
     try_use_solver!(
         puzzle_key,
         2024,
@@ -130,27 +332,7 @@ fn get_solver(puzzle_key: &PuzzleKey) -> Box<dyn Fn(&str) -> String> {
         Part::Three,
         event2024::quest4::solve_part_3
     );
-    try_use_solver!(
-        puzzle_key,
-        2024,
-        5,
-        Part::One,
-        event2024::quest5::solve_part_1
-    );
-    try_use_solver!(
-        puzzle_key,
-        2024,
-        5,
-        Part::Two,
-        event2024::quest5::solve_part_2
-    );
-    try_use_solver!(
-        puzzle_key,
-        2024,
-        5,
-        Part::Three,
-        event2024::quest5::solve_part_3
-    );
+    try_use_quest!(puzzle_key, 2024, 5, event2024::quest5::Dance);
     try_use_solver!(
         puzzle_key,
         2024,
@@ -179,12 +361,14 @@ fn get_solver(puzzle_key: &PuzzleKey) -> Box<dyn Fn(&str) -> String> {
         Part::One,
         event2024::quest7::solve_part_1
     );
-    try_use_solver!(
+    try_use_param_solver!(
         puzzle_key,
+        params,
         2024,
         7,
         Part::Two,
-        event2024::quest7::solve_part_2
+        event2024::quest7::solve_part_2,
+        [event2024::quest7::TRACK]
     );
     try_use_solver!(
         puzzle_key,
@@ -200,19 +384,29 @@ fn get_solver(puzzle_key: &PuzzleKey) -> Box<dyn Fn(&str) -> String> {
         Part::One,
         event2024::quest8::solve_part_1
     );
-    try_use_solver!(
+    try_use_param_solver!(
         puzzle_key,
+        params,
         2024,
         8,
         Part::Two,
-        event2024::quest8::solve_part_2
+        event2024::quest8::solve_part_2,
+        [
+            event2024::quest8::PART_2_ACOLYTES,
+            event2024::quest8::PART_2_BLOCKS
+        ]
     );
-    try_use_solver!(
+    try_use_param_solver!(
         puzzle_key,
+        params,
         2024,
         8,
         Part::Three,
-        event2024::quest8::solve_part_3
+        event2024::quest8::solve_part_3,
+        [
+            event2024::quest8::PART_3_ACOLYTES,
+            event2024::quest8::PART_3_BLOCKS
+        ]
     );
     try_use_solver!(
         puzzle_key,
@@ -368,12 +562,14 @@ fn get_solver(puzzle_key: &PuzzleKey) -> Box<dyn Fn(&str) -> String> {
         Part::One,
         event2024::quest16::solve_part_1
     );
-    try_use_solver!(
+    try_use_param_solver!(
         puzzle_key,
+        params,
         2024,
         16,
         Part::Two,
-        event2024::quest16::solve_part_2
+        event2024::quest16::solve_part_2,
+        [event2024::quest16::COUNT]
     );
     try_use_solver!(
         puzzle_key,
@@ -622,27 +818,7 @@ fn get_solver(puzzle_key: &PuzzleKey) -> Box<dyn Fn(&str) -> String> {
         Part::Three,
         event2025::quest6::solve_part_3
     );
-    try_use_solver!(
-        puzzle_key,
-        2025,
-        7,
-        Part::One,
-        event2025::quest7::solve_part_1
-    );
-    try_use_solver!(
-        puzzle_key,
-        2025,
-        7,
-        Part::Two,
-        event2025::quest7::solve_part_2
-    );
-    try_use_solver!(
-        puzzle_key,
-        2025,
-        7,
-        Part::Three,
-        event2025::quest7::solve_part_3
-    );
+    try_use_quest!(puzzle_key, 2025, 7, event2025::quest7::Names);
     try_use_solver!(
         puzzle_key,
         2025,
@@ -692,12 +868,14 @@ fn get_solver(puzzle_key: &PuzzleKey) -> Box<dyn Fn(&str) -> String> {
         Part::One,
         event2025::quest10::solve_part_1
     );
-    try_use_solver!(
+    try_use_param_solver!(
         puzzle_key,
+        params,
         2025,
         10,
         Part::Two,
-        event2025::quest10::solve_part_2
+        event2025::quest10::solve_part_2,
+        [event2025::quest10::TURNS]
     );
     try_use_solver!(
         puzzle_key,
@@ -755,12 +933,14 @@ fn get_solver(puzzle_key: &PuzzleKey) -> Box<dyn Fn(&str) -> String> {
         Part::One,
         event2025::quest13::solve_part_1
     );
-    try_use_solver!(
+    try_use_param_solver!(
         puzzle_key,
+        params,
         2025,
         13,
         Part::Two,
-        event2025::quest13::solve_part_2
+        event2025::quest13::solve_part_2,
+        [event2025::quest13::TURNS]
     );
     try_use_solver!(
         puzzle_key,
@@ -916,98 +1096,420 @@ fn get_solver(puzzle_key: &PuzzleKey) -> Box<dyn Fn(&str) -> String> {
         Part::Three,
         event2025::quest20::solve_part_3
     );
-    panic!("solver not found for {:?}", puzzle_key);
+    None
+}
+
+/// All the puzzles with a registered solver, sorted.
+fn registered_keys() -> Vec<PuzzleKey> {
+    EVENTS
+        .into_iter()
+        .flat_map(|event| {
+            let quests = EventKind::of(event).unwrap().quests();
+            quests.flat_map(move |quest| Part::ALL.map(|part| PuzzleKey { event, quest, part }))
+        })
+        .filter(|key| find_solver(key, &Params::default()).is_some())
+        .collect()
+}
+
+fn get_solver(puzzle_key: &PuzzleKey, params: &Params) -> Box<dyn Solver> {
+    find_solver(puzzle_key, params).unwrap_or_else(|| {
+        panic!(
+            "solver not found for {puzzle_key}, it belongs in {}",
+            puzzle_key.source_path()
+        )
+    })
+}
+
+/// The parameters given with `--param`.
+fn params(assignments: &[(String, String)]) -> Params {
+    Params::from_iter(
+        assignments
+            .iter()
+            .map(|(name, value)| (name.as_str(), value.as_str())),
+    )
+}
+
+/// The first parameter which is not among the accepted ones, if any.
+fn unknown_param<'a>(params: &'a Params, accepted: &[&str]) -> Option<&'a str> {
+    params.names().find(|name| !accepted.contains(name))
+}
+
+/// Lists the parameters one or several solvers accept for an error message, e.g.
+/// `it accepts: turns`.
+fn describe_params(accepted: &[&str], several: bool) -> String {
+    let (subject, s) = if several { ("they", "") } else { ("it", "s") };
+    let mut names = accepted.to_vec();
+    names.sort();
+    names.dedup();
+    match names[..] {
+        [] => format!("{subject} take{s} no parameters"),
+        _ => format!("{subject} accept{s}: {}", names.join(", ")),
+    }
+}
+
+fn report_submission_result(result: SubmissionResult) {
+    if result.cached {
+        log::info!("submission result was provided by the submission log");
+    }
+    if result.is_answer_correct.unwrap() {
+        log::info!("✅ the answer is correct!");
+        if let Some(details) = result.details {
+            log::info!(
+                "time since event start: {}",
+                pretty_duration(&details.global_time, None)
+            );
+            log::info!(
+                "time since quest opened: {}",
+                pretty_duration(&details.local_time, None)
+            );
+            log::info!(
+                "global score {} (rank {})",
+                details.global_score,
+                details.global_place
+            );
+        }
+    } else {
+        log::info!("❌ the answer was NOT correct, try harder");
+        log::info!(
+            "the first letter of the answer was {}",
+            match result.is_first_character_correct {
+                None => "[no data]",
+                Some(true) => "✅️ correct",
+                Some(false) => "❌ not correct",
+            }
+        );
+        log::info!(
+            "the answer length was {}",
+            match result.is_length_correct {
+                None => "[no data]",
+                Some(true) => "✅️ correct",
+                Some(false) => "❌ not correct",
+            }
+        );
+    }
+}
+
+fn export_stats(
+    results: PathBuf,
+    user_id: Option<i64>,
+    csv: Option<PathBuf>,
+    markdown: Option<PathBuf>,
+    svg: Option<PathBuf>,
+) {
+    let log = submissionstore::open_store(&results)
+        .load()
+        .expect("accessing the submission log")
+        .unwrap_or_default();
+    let stats = stats::collect(&log, user_id);
+    if csv.is_none() && markdown.is_none() && svg.is_none() {
+        print!("{}", stats::to_markdown(&stats));
+        return;
+    }
+    let outputs = [
+        (csv, stats::to_csv as fn(&[stats::PartStats]) -> String),
+        (markdown, stats::to_markdown),
+        (svg, stats::to_svg),
+    ];
+    for (path, export) in outputs {
+        if let Some(path) = path {
+            log::info!("writing {}", path.display());
+            fs::write(&path, export(&stats)).expect("writing the statistics");
+        }
+    }
+}
+
+fn print_status(selector: Option<Selector>, results: PathBuf, user_id: Option<i64>) {
+    let log = submissionstore::open_store(&results)
+        .load()
+        .expect("accessing the submission log")
+        .unwrap_or_default();
+    let solved = stats::collect(&log, user_id)
+        .into_iter()
+        .filter(|s| s.accepted_answer.is_some())
+        .filter_map(|s| PuzzleKey::new(s.event, s.quest, Part::try_from(s.part).ok()?).ok())
+        .collect();
+    let registered: HashSet<PuzzleKey> = registered_keys().into_iter().collect();
+    print!(
+        "{}",
+        status::report(
+            EVENTS,
+            selector.as_ref(),
+            |key| registered.contains(key),
+            &solved
+        )
+    );
+}
+
+fn create_event(event: i16, src: PathBuf) {
+    let written = scaffold::scaffold_event(&src, event).unwrap_or_else(|e| {
+        Args::command()
+            .error(clap::error::ErrorKind::ValueValidation, e)
+            .exit()
+    });
+    for path in &written {
+        log::info!("wrote {}", path.display());
+    }
+    if !EVENTS.contains(&event) {
+        log::info!(
+            "declare mod {}; in src/main.rs and add {event} to EVENTS",
+            types::module_name(event)
+        );
+    }
+    log::info!("register the solvers in find_solver as you write them");
+}
+
+fn export_team_results(results: &[PathBuf], csv: Option<PathBuf>, markdown: Option<PathBuf>) {
+    let boards = team::leaderboards(team::load(results).expect("accessing the submission logs"));
+    if csv.is_none() && markdown.is_none() {
+        print!("{}", team::to_markdown(&boards));
+        return;
+    }
+    let outputs = [
+        (csv, team::to_csv as fn(&[team::Leaderboard]) -> String),
+        (markdown, team::to_markdown),
+    ];
+    for (path, export) in outputs {
+        if let Some(path) = path {
+            log::info!("writing {}", path.display());
+            fs::write(&path, export(&boards)).expect("writing the leaderboard");
+        }
+    }
+}
+
+/// Runs the solver, logging the candidate answers and how long it took.
+fn solve(
+    solver: &dyn Solver,
+    input: &str,
+    timeout: Option<Duration>,
+) -> Option<(Vec<String>, Duration)> {
+    log::info!("solving...");
+    let solve_start = Instant::now();
+    let result = cancel::run(timeout, || solver.solve_candidates(input));
+    trace::flush();
+    let candidates: Vec<String> = match result {
+        Ok(Ok(answers)) => answers.iter().map(|a| a.to_string()).collect(),
+        Ok(Err(e)) => {
+            log::error!("the solver failed: {e}");
+            return None;
+        }
+        Err(cancelled) => {
+            log::error!("the solver was {cancelled}");
+            return None;
+        }
+    };
+    let solve_duration = solve_start.elapsed();
+    log::info!(
+        "solution: {} (solved in {})",
+        candidates.join(" or "),
+        pretty_duration(&solve_duration, None)
+    );
+    Some((candidates, solve_duration))
 }
 
 fn main() {
-    pretty_env_logger::init();
+    logging::init();
     log::info!("Everybody Codes solver");
 
     let args = Args::parse();
+    progress::set_mode(args.progress);
+    let timeout = args.timeout.map(Duration::from_secs);
+    if let Some(ref path) = args.trace
+        && let Err(e) = trace::open(path)
+    {
+        Args::command()
+            .error(
+                clap::error::ErrorKind::Io,
+                format!("cannot create {}: {e}", path.display()),
+            )
+            .exit();
+    }
+    match args.command {
+        Some(Command::Stats {
+            results,
+            user_id,
+            csv,
+            markdown,
+            svg,
+        }) => {
+            export_stats(results, user_id, csv, markdown, svg);
+            return;
+        }
+        Some(Command::Team {
+            results,
+            csv,
+            markdown,
+        }) => {
+            export_team_results(&results, csv, markdown);
+            return;
+        }
+        Some(Command::List { selector }) => {
+            let keys = registered_keys();
+            let keys = match selector {
+                Some(selector) => selector.expand(keys),
+                None => keys,
+            };
+            for key in keys {
+                println!("{key}");
+            }
+            return;
+        }
+        Some(Command::Status {
+            selector,
+            results,
+            user_id,
+        }) => {
+            print_status(selector, results, user_id);
+            return;
+        }
+        Some(Command::New { event, src }) => {
+            create_event(event, src);
+            return;
+        }
+        Some(Command::Run { selector, input }) => {
+            let input = fs::read_to_string(&input).expect("reading the input file");
+            let params = params(&args.params);
+            let solvers: Vec<_> = selector
+                .expand(registered_keys())
+                .into_iter()
+                .map(|key| (key, get_solver(&key, &params)))
+                .collect();
+            let accepted: Vec<&str> = solvers
+                .iter()
+                .flat_map(|(_, solver)| solver.param_names().iter().copied())
+                .collect();
+            if let Some(name) = unknown_param(&params, &accepted) {
+                Args::command()
+                    .error(
+                        clap::error::ErrorKind::InvalidValue,
+                        format!(
+                            "none of the selected solvers has a parameter {name:?}, {}",
+                            describe_params(&accepted, true)
+                        ),
+                    )
+                    .exit();
+            }
+            for (key, solver) in solvers {
+                log::info!("solving {key}");
+                trace::set_puzzle(key);
+                if let Some((candidates, _)) = solve(solver.as_ref(), input.as_str(), timeout) {
+                    println!("{key}: {}", candidates.join(" or "));
+                }
+            }
+            return;
+        }
+        None => {}
+    }
+    let (Some(event), Some(quest), Some(part)) = (args.event, args.quest, args.part) else {
+        unreachable!("clap requires the puzzle when no command is given");
+    };
+    let (key, kind) = PuzzleKey::new(event, quest, part)
+        .and_then(|key| Ok((key, key.event_kind()?)))
+        .unwrap_or_else(|e| {
+            Args::command()
+                .error(clap::error::ErrorKind::ValueValidation, e)
+                .exit()
+        });
+    let params = params(&args.params);
+    let solver = get_solver(&key, &params);
+    trace::set_puzzle(key);
+    if let Some(name) = unknown_param(&params, solver.param_names()) {
+        Args::command()
+            .error(
+                clap::error::ErrorKind::InvalidValue,
+                format!(
+                    "the solver for {key} has no parameter {name:?}, {}",
+                    describe_params(solver.param_names(), false)
+                ),
+            )
+            .exit();
+    }
+    if let Some(path) = args.input {
+        let input = fs::read_to_string(&path).expect("reading the input file");
+        if let Some((candidates, _)) = solve(solver.as_ref(), input.as_str(), timeout) {
+            println!("{}", candidates.join("\n"));
+        }
+        return;
+    }
 
     // Get the EC cookie, either from the command line, or from the env variable.
+    let cookie_var = match &args.user {
+        Some(user) => format!("EC_COOKIE_{}", user.to_uppercase()),
+        None => String::from("EC_COOKIE"),
+    };
     let cookie = if let Some(cookie) = args.cookie {
         cookie
-    } else if let Ok(cookie) = env::var("EC_COOKIE") {
+    } else if let Ok(cookie) = env::var(cookie_var.as_str()) {
         cookie
     } else {
-        panic!("you must specify the session cookie with --cookie or EC_COOKIE env variable");
-    };
-    let key = types::PuzzleKey {
-        event: args.event,
-        quest: args.quest,
-        part: match args.part {
-            1 => types::Part::One,
-            2 => types::Part::Two,
-            3 => types::Part::Three,
-            _ => panic!("part should be in [1,2,3], got {}", args.part),
-        },
+        panic!("you must specify the session cookie with --cookie or {cookie_var} env variable");
     };
-    let solver = get_solver(&key);
-    let client = ecclient::EcClient::new(cookie.as_str()).expect("creating EC client");
+    let client = ecclient::EcClient::new(
+        ecclient::SessionCookie::new(cookie.as_str()),
+        args.verbose_http,
+    )
+    .expect("creating EC client");
     log::info!(
-        "solving Everybody Codes event {} quest {} part {}",
-        args.event,
-        args.quest,
-        args.part
+        "solving Everybody Codes {} quest {} part {}",
+        kind.name(key.event),
+        key.quest,
+        key.part
     );
     log::info!("retrieving puzzle input...");
     match client.get_puzzle_input(&key) {
         Ok(input) => {
-            log::info!("solving...");
-            let solution = solver(input.as_str());
-            log::info!("solution: {}", solution);
+            let Some((candidates, solve_duration)) =
+                solve(solver.as_ref(), input.as_str(), timeout)
+            else {
+                return;
+            };
             if args.submit {
-                if solution.is_empty() {
-                    log::warn!("refusing to submit an empty solution");
-                } else {
-                    let result = submit_with_cache(&key, solution.as_str(), |key, answer| {
-                        if let Some(delay) = client.get_penalty_delay().unwrap() {
-                            log::info!("sleeping for {:?} before submitting...", &delay);
-                            thread::sleep(delay);
+                let store = submissionstore::open_store(&args.results);
+                let mut valid_candidates = vec![];
+                for candidate in candidates {
+                    let problems = autosubmit::validate_answer(
+                        store.as_ref(),
+                        client.user_id(),
+                        &key,
+                        &candidate,
+                    )
+                    .expect("accessing the submission log");
+                    for problem in &problems {
+                        if problem.is_rejected() {
+                            log::error!("{candidate:?}: {problem}");
+                        } else {
+                            log::warn!("{candidate:?}: {problem}");
                         }
-                        log::info!("submitting the answer to the server...");
-                        client.post_answer(key, answer).unwrap()
-                    });
-                    if result.cached {
-                        log::info!("submission result was provided by the cache in results.toml");
                     }
-                    if result.is_answer_correct.unwrap() {
-                        log::info!("✅ the answer is correct!");
-                        if let Some(details) = result.details {
-                            log::info!(
-                                "time since event start: {}",
-                                pretty_duration(&details.global_time, None)
-                            );
-                            log::info!(
-                                "time since quest opened: {}",
-                                pretty_duration(&details.local_time, None)
-                            );
+                    if !args.force && problems.iter().any(|p| p.is_rejected()) {
+                        log::warn!("refusing to submit {candidate:?}, use --force to override");
+                    } else {
+                        valid_candidates.push(candidate);
+                    }
+                }
+                if valid_candidates.is_empty() {
+                    log::warn!("no answer left to submit");
+                }
+                let results = submit_candidates(
+                    store.as_ref(),
+                    client.user_id(),
+                    &key,
+                    &valid_candidates,
+                    Some(solve_duration),
+                    |key, answer| {
+                        if let Some(delay) = client.get_penalty_delay().unwrap() {
                             log::info!(
-                                "global score {} (rank {})",
-                                details.global_score,
-                                details.global_place
+                                "penalty expires in {}, the answer will be submitted then...",
+                                pretty_duration(&delay, None)
                             );
                         }
-                    } else {
-                        log::info!("❌ the answer was NOT correct, try harder");
-                        log::info!(
-                            "the first letter of the answer was {}",
-                            match result.is_first_character_correct {
-                                None => "[no data]",
-                                Some(true) => "✅️ correct",
-                                Some(false) => "❌ not correct",
-                            }
-                        );
-                        log::info!(
-                            "the answer length was {}",
-                            match result.is_length_correct {
-                                None => "[no data]",
-                                Some(true) => "✅️ correct",
-                                Some(false) => "❌ not correct",
-                            }
-                        );
-                    }
+                        log::info!("submitting {answer} to the server...");
+                        client.post_answer_when_allowed(key, answer).unwrap()
+                    },
+                )
+                .expect("accessing the submission log");
+                for (answer, result) in results {
+                    log::info!("result for {answer}:");
+                    report_submission_result(result);
                 }
             }
         }
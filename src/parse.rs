@@ -0,0 +1,242 @@
+use std::str::FromStr;
+
+use regex::Captures;
+use regex::Regex;
+
+use crate::solver::SolveError;
+
+/// A puzzle input, with helpers for the formats the quests use. Every helper takes a
+/// slice of the input, so that a problem is reported at its line and column, e.g.
+/// `line 12, col 7: expected integer, found "x"`.
+#[derive(Clone, Copy)]
+pub struct Input<'a> {
+    text: &'a str,
+}
+
+impl<'a> Input<'a> {
+    pub fn new(text: &'a str) -> Input<'a> {
+        Input { text }
+    }
+
+    /// Reports a problem with `fragment`, a slice of the input.
+    pub fn error(&self, fragment: &str, message: impl Into<String>) -> SolveError {
+        SolveError::at_fragment(self.text, fragment, message)
+    }
+
+    pub fn lines(&self) -> impl Iterator<Item = &'a str> {
+        self.text.lines()
+    }
+
+    /// The blocks of the input separated by blank lines.
+    pub fn sections(&self) -> Vec<&'a str> {
+        self.text
+            .split("\n\n")
+            .map(|section| section.trim_end_matches('\n'))
+            .filter(|section| !section.is_empty())
+            .collect()
+    }
+
+    /// Exactly two blocks separated by a blank line.
+    pub fn two_sections(&self) -> Result<(&'a str, &'a str), SolveError> {
+        match self.sections()[..] {
+            [first, second] => Ok((first, second)),
+            ref sections => Err(SolveError::new(format!(
+                "expected 2 sections separated by a blank line, found {}",
+                sections.len()
+            ))),
+        }
+    }
+
+    pub fn integer<T: FromStr>(&self, fragment: &'a str) -> Result<T, SolveError> {
+        fragment
+            .parse()
+            .map_err(|_| self.error(fragment, format!("expected integer, found {fragment:?}")))
+    }
+
+    /// A comma separated list such as `1,2,3`.
+    pub fn comma_list<T: FromStr>(&self, fragment: &'a str) -> Result<Vec<T>, SolveError> {
+        fragment.split(',').map(|item| self.integer(item)).collect()
+    }
+
+    /// A line such as `A:B,C` listing the nodes a node leads to.
+    pub fn adjacency(&self, line: &'a str) -> Result<(&'a str, Vec<&'a str>), SolveError> {
+        let (node, edges) = line
+            .split_once(':')
+            .ok_or_else(|| self.error(line, format!("expected node:a,b,c, found {line:?}")))?;
+        Ok((node, edges.split(',').collect()))
+    }
+
+    /// A comma separated list of instructions such as `L3,R5`: a letter followed by an
+    /// integer.
+    pub fn instructions<T: FromStr>(
+        &self,
+        fragment: &'a str,
+    ) -> Result<Vec<(char, T)>, SolveError> {
+        self.instructions_with(fragment, |_, letter| Ok(letter))
+    }
+
+    /// Like `instructions`, but converts each letter with `letter`, which is given the
+    /// instruction it came from for error reporting.
+    pub fn instructions_with<L, T: FromStr>(
+        &self,
+        fragment: &'a str,
+        mut letter: impl FnMut(&'a str, char) -> Result<L, SolveError>,
+    ) -> Result<Vec<(L, T)>, SolveError> {
+        fragment
+            .split(',')
+            .map(|token| {
+                let mut chars = token.chars();
+                match chars.next() {
+                    Some(c) if c.is_ascii_alphabetic() => {
+                        Ok((letter(token, c)?, self.integer(chars.as_str())?))
+                    }
+                    _ => Err(self.error(token, format!("expected instruction, found {token:?}"))),
+                }
+            })
+            .collect()
+    }
+
+    /// A grid of single digits, one row per line.
+    pub fn digit_grid(&self, fragment: &'a str) -> Result<Vec<Vec<u8>>, SolveError> {
+        fragment
+            .lines()
+            .map(|line| {
+                line.char_indices()
+                    .map(|(i, c)| {
+                        c.to_digit(10).map(|d| d as u8).ok_or_else(|| {
+                            self.error(&line[i..], format!("expected digit, found {c:?}"))
+                        })
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Matches the regex against the fragment, which must match.
+    pub fn captures(&self, re: &Regex, fragment: &'a str) -> Result<Captures<'a>, SolveError> {
+        re.captures(fragment).ok_or_else(|| {
+            self.error(
+                fragment,
+                format!("expected to match {}, found {fragment:?}", re.as_str()),
+            )
+        })
+    }
+
+    /// Parses the numbered capture group as an integer.
+    pub fn capture<T: FromStr>(
+        &self,
+        captures: &Captures<'a>,
+        group: usize,
+    ) -> Result<T, SolveError> {
+        let group = captures
+            .get(group)
+            .ok_or_else(|| SolveError::new(format!("no capture group {group}")))?;
+        self.integer(group.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_integer() {
+        let input = Input::new("1,2\n3,x");
+        assert_eq!(Ok(vec![1, 2]), input.comma_list::<i32>(&"1,2\n3,x"[..3]));
+        assert_eq!(
+            "line 2, col 3: expected integer, found \"x\"",
+            input
+                .comma_list::<i32>(&"1,2\n3,x"[4..])
+                .unwrap_err()
+                .to_string()
+        );
+    }
+
+    #[test]
+    fn test_sections() {
+        let input = Input::new("a,b\n\nc\nd\n");
+        assert_eq!(vec!["a,b", "c\nd"], input.sections());
+        assert_eq!(Ok(("a,b", "c\nd")), input.two_sections());
+        assert!(Input::new("a").two_sections().is_err());
+    }
+
+    #[test]
+    fn test_adjacency() {
+        let text = "A:B,C\nB";
+        let input = Input::new(text);
+        let lines: Vec<&str> = input.lines().collect();
+        assert_eq!(Ok(("A", vec!["B", "C"])), input.adjacency(lines[0]));
+        assert_eq!(
+            "line 2, col 1: expected node:a,b,c, found \"B\"",
+            input.adjacency(lines[1]).unwrap_err().to_string()
+        );
+    }
+
+    #[test]
+    fn test_instructions() {
+        let text = "L3,R5,5,R?";
+        let input = Input::new(text);
+        assert_eq!(
+            Ok(vec![('L', 3), ('R', 5)]),
+            input.instructions::<u32>(&text[..5])
+        );
+        assert_eq!(
+            "line 1, col 7: expected instruction, found \"5\"",
+            input
+                .instructions::<u32>(&text[6..7])
+                .unwrap_err()
+                .to_string()
+        );
+        assert_eq!(
+            "line 1, col 10: expected integer, found \"?\"",
+            input
+                .instructions::<u32>(&text[8..])
+                .unwrap_err()
+                .to_string()
+        );
+        let text = "L3,R5,X5";
+        let input = Input::new(text);
+        let sign = |token: &str, letter| match letter {
+            'L' => Ok(-1),
+            'R' => Ok(1),
+            _ => Err(input.error(token, format!("expected L or R, found {letter:?}"))),
+        };
+        assert_eq!(
+            Ok(vec![(-1, 3), (1, 5)]),
+            input.instructions_with::<_, i32>(&text[..5], sign)
+        );
+        assert_eq!(
+            "line 1, col 7: expected L or R, found 'X'",
+            input
+                .instructions_with::<_, i32>(text, sign)
+                .unwrap_err()
+                .to_string()
+        );
+    }
+
+    #[test]
+    fn test_digit_grid() {
+        let text = "12\n3a";
+        let input = Input::new(text);
+        assert_eq!(Ok(vec![vec![1, 2]]), input.digit_grid(&text[..2]));
+        assert_eq!(
+            "line 2, col 2: expected digit, found 'a'",
+            input.digit_grid(text).unwrap_err().to_string()
+        );
+    }
+
+    #[test]
+    fn test_captures() {
+        let text = "x=1 y=2\nx=3 y=?";
+        let input = Input::new(text);
+        let re = Regex::new(r"x=(\d+) y=(\d+)").unwrap();
+        let lines: Vec<&str> = input.lines().collect();
+        let captures = input.captures(&re, lines[0]).unwrap();
+        assert_eq!(Ok(2), input.capture::<i64>(&captures, 2));
+        assert!(input.capture::<i64>(&captures, 3).is_err());
+        assert_eq!(
+            r#"line 2, col 1: expected to match x=(\d+) y=(\d+), found "x=3 y=?""#,
+            input.captures(&re, lines[1]).unwrap_err().to_string()
+        );
+    }
+}
@@ -0,0 +1,302 @@
+use std::io::Write;
+use std::io::stderr;
+use std::sync::Mutex;
+use std::sync::RwLock;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+use std::time::Instant;
+
+use clap::ValueEnum;
+
+use crate::cancel;
+use crate::cancel::Cancelled;
+
+/// How progress is reported.
+#[derive(Clone, Copy, Debug, Default, PartialEq, ValueEnum)]
+pub enum Mode {
+    /// Not at all.
+    Off,
+    /// As debug log messages every few seconds.
+    #[default]
+    Log,
+    /// As a progress line on stderr, redrawn in place.
+    Line,
+    /// As JSON events on stderr, one per line.
+    Json,
+}
+
+impl Mode {
+    /// How often a progress report is rendered.
+    fn interval(self) -> Duration {
+        match self {
+            Mode::Off | Mode::Log => Duration::from_secs(10),
+            Mode::Line => Duration::from_millis(200),
+            Mode::Json => Duration::from_secs(1),
+        }
+    }
+}
+
+static MODE: RwLock<Mode> = RwLock::new(Mode::Log);
+
+pub fn set_mode(mode: Mode) {
+    *MODE.write().unwrap() = mode;
+}
+
+fn mode() -> Mode {
+    *MODE.read().unwrap()
+}
+
+/// The state of a counter at some point, as rendered.
+#[derive(Debug, PartialEq)]
+pub struct Snapshot {
+    pub name: String,
+    pub count: u64,
+    pub total: Option<u64>,
+    /// Steps per second since the counter was created.
+    pub rate: f64,
+    /// Estimated time left, if the total is known.
+    pub eta: Option<Duration>,
+    pub message: String,
+}
+
+impl Snapshot {
+    pub fn render_line(&self) -> String {
+        let mut line = format!("{}: {}", self.name, self.count);
+        if let Some(total) = self.total {
+            let percent = 100. * self.count as f64 / total.max(1) as f64;
+            line += &format!("/{total} ({percent:.1}%)");
+        }
+        line += &format!(" {:.2}/s", self.rate);
+        if let Some(eta) = self.eta {
+            line += &format!(" ETA {}s", eta.as_secs());
+        }
+        if !self.message.is_empty() {
+            line += &format!(" {}", self.message);
+        }
+        line
+    }
+
+    fn render_json(&self, event: &str) -> String {
+        serde_json::json!({
+            "event": event,
+            "name": self.name,
+            "count": self.count,
+            "total": self.total,
+            "rate": self.rate,
+            "eta_secs": self.eta.map(|eta| eta.as_secs_f64()),
+            "message": self.message,
+        })
+        .to_string()
+    }
+}
+
+/// A named counter of the steps a long computation has made, which reports its rate
+/// and, when the total is known, the time left. It can be shared between threads.
+pub struct Progress {
+    name: String,
+    total: Option<u64>,
+    started: Instant,
+    count: AtomicU64,
+    /// The count at which to check the clock again.
+    next_check: AtomicU64,
+    last_report: Mutex<Instant>,
+}
+
+impl Progress {
+    pub fn new(name: impl Into<String>) -> Progress {
+        let now = Instant::now();
+        Progress {
+            name: name.into(),
+            total: None,
+            started: now,
+            count: AtomicU64::new(0),
+            next_check: AtomicU64::new(100),
+            last_report: Mutex::new(now),
+        }
+    }
+
+    pub fn with_total(name: impl Into<String>, total: u64) -> Progress {
+        Progress {
+            total: Some(total),
+            ..Progress::new(name)
+        }
+    }
+
+    pub fn count(&self) -> u64 {
+        self.count.load(Ordering::Relaxed)
+    }
+
+    /// Counts one step.
+    pub fn inc(&self) {
+        self.inc_with(1, String::new);
+    }
+
+    /// Counts `steps` steps, and reports progress if it is time to. The message is only
+    /// built when a report is rendered. Stops the solver if it has been cancelled.
+    pub fn inc_with(&self, steps: u64, message: impl FnOnce() -> String) {
+        let count = self.count.fetch_add(steps, Ordering::Relaxed) + steps;
+        if count < self.next_check.load(Ordering::Relaxed) {
+            return;
+        }
+        let token = cancel::current();
+        if let Some(reason) = token.reason() {
+            if mode() == Mode::Line {
+                let _ = writeln!(stderr());
+            }
+            cancel::stop(Cancelled {
+                reason,
+                elapsed: token.elapsed(),
+                progress: Some(self.snapshot_at(Instant::now(), message())),
+            });
+        }
+        // Only one thread reports at a time, the others carry on counting.
+        let Ok(mut last_report) = self.last_report.try_lock() else {
+            return;
+        };
+        let mode = mode();
+        let now = Instant::now();
+        // Check the clock again after about a tenth of the reporting interval.
+        let steps_to_next_check = (self.rate_at(now) * mode.interval().as_secs_f64() / 10.) as u64;
+        self.next_check
+            .store(count + steps_to_next_check.max(1), Ordering::Relaxed);
+        if mode == Mode::Off || now.duration_since(*last_report) < mode.interval() {
+            return;
+        }
+        *last_report = now;
+        render(mode, "progress", &self.snapshot_at(now, message()));
+    }
+
+    /// Reports the final count.
+    pub fn finish(&self) {
+        let mode = mode();
+        if mode != Mode::Off {
+            render(
+                mode,
+                "finish",
+                &self.snapshot_at(Instant::now(), String::new()),
+            );
+        }
+    }
+
+    /// Steps per second from the start until `now`.
+    fn rate_at(&self, now: Instant) -> f64 {
+        let elapsed = now.duration_since(self.started).as_secs_f64();
+        if elapsed > 0. {
+            self.count() as f64 / elapsed
+        } else {
+            0.
+        }
+    }
+
+    pub fn snapshot_at(&self, now: Instant, message: String) -> Snapshot {
+        let count = self.count();
+        let rate = self.rate_at(now);
+        let eta = self.total.and_then(|total| {
+            (rate > 0.).then(|| Duration::from_secs_f64(total.saturating_sub(count) as f64 / rate))
+        });
+        Snapshot {
+            name: self.name.clone(),
+            count,
+            total: self.total,
+            rate,
+            eta,
+            message,
+        }
+    }
+}
+
+fn render(mode: Mode, event: &str, snapshot: &Snapshot) {
+    match mode {
+        Mode::Off => {}
+        Mode::Log => log::debug!("{}", snapshot.render_line()),
+        Mode::Line => {
+            let end = if event == "finish" { "\n" } else { "" };
+            let _ = write!(stderr(), "\r\x1b[K{}{end}", snapshot.render_line());
+        }
+        Mode::Json => {
+            let _ = writeln!(stderr(), "{}", snapshot.render_json(event));
+        }
+    }
+}
+
+/// Counts a step of the loop it is called in and reports the rate now and then, e.g.
+/// `debug_with_rate!("{t}")`. Each call site has its own counter.
+#[macro_export]
+macro_rules! debug_with_rate {
+    ($($arg:tt)+) => {{
+        static PROGRESS: std::sync::LazyLock<$crate::progress::Progress> =
+            std::sync::LazyLock::new(|| {
+                $crate::progress::Progress::new(concat!(module_path!(), ":", line!()))
+            });
+        PROGRESS.inc_with(1, || format!($($arg)+));
+    }};
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use std::thread;
+
+    use super::*;
+
+    #[test]
+    fn test_count_across_threads() {
+        let progress = Arc::new(Progress::new("test"));
+        let threads: Vec<_> = (0..4)
+            .map(|_| {
+                let progress = progress.clone();
+                thread::spawn(move || {
+                    for i in 0..10_000 {
+                        progress.inc_with(1, || format!("{i}"));
+                    }
+                })
+            })
+            .collect();
+        for thread in threads {
+            thread.join().unwrap();
+        }
+        assert_eq!(40_000, progress.count());
+    }
+
+    #[test]
+    fn test_rate_and_eta() {
+        let progress = Progress::with_total("search", 100);
+        progress.inc_with(25, String::new);
+        let snapshot = progress.snapshot_at(
+            progress.started + Duration::from_secs(5),
+            String::from("t=3"),
+        );
+        assert_eq!(5., snapshot.rate);
+        assert_eq!(Some(Duration::from_secs(15)), snapshot.eta);
+        assert_eq!(
+            "search: 25/100 (25.0%) 5.00/s ETA 15s t=3",
+            snapshot.render_line()
+        );
+        assert_eq!(
+            r#"{"count":25,"eta_secs":15.0,"event":"progress","message":"t=3","name":"search","rate":5.0,"total":100}"#,
+            snapshot.render_json("progress")
+        );
+    }
+
+    #[test]
+    fn test_cancel() {
+        let progress = Progress::new("search");
+        let cancelled = cancel::run(Some(Duration::ZERO), || {
+            loop {
+                progress.inc_with(1, || String::from("still searching"));
+            }
+        })
+        .unwrap_err();
+        let snapshot = cancelled.progress.unwrap();
+        assert_eq!(100, snapshot.count);
+        assert_eq!("still searching", snapshot.message);
+    }
+
+    #[test]
+    fn test_macro() {
+        for i in 0..3 {
+            debug_with_rate!("{i}");
+        }
+    }
+}
@@ -0,0 +1,140 @@
+use std::any::Any;
+use std::any::TypeId;
+use std::collections::HashMap;
+use std::marker::PhantomData;
+use std::sync::Arc;
+use std::sync::LazyLock;
+use std::sync::Mutex;
+
+use crate::solver::Answer;
+use crate::solver::SolveError;
+use crate::solver::Solver;
+use crate::types::Part;
+
+/// A quest whose input is parsed once into a typed model, from which all three parts
+/// are answered. Implementing it is optional: plain solver functions still work.
+pub trait Quest: 'static {
+    type Model: Send + Sync + 'static;
+
+    fn parse(input: &str) -> Result<Self::Model, SolveError>;
+
+    fn part_1(model: &Self::Model) -> Result<Answer, SolveError>;
+
+    fn part_2(model: &Self::Model) -> Result<Answer, SolveError>;
+
+    fn part_3(model: &Self::Model) -> Result<Answer, SolveError>;
+}
+
+type ModelCache = HashMap<(TypeId, String), Arc<dyn Any + Send + Sync>>;
+
+/// Models already parsed, by quest and input, so that solving several parts with the
+/// same input parses it only once.
+static MODELS: LazyLock<Mutex<ModelCache>> = LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// Parses the input, or returns the model parsed earlier from the same input.
+pub fn parse_cached<Q: Quest>(input: &str) -> Result<Arc<Q::Model>, SolveError> {
+    let key = (TypeId::of::<Q>(), input.to_string());
+    if let Some(model) = MODELS.lock().unwrap().get(&key) {
+        return Ok(Arc::clone(model)
+            .downcast()
+            .expect("models are cached by quest type"));
+    }
+    let model = Arc::new(Q::parse(input)?);
+    MODELS.lock().unwrap().insert(key, model.clone());
+    Ok(model)
+}
+
+/// Answers one part of the quest.
+pub fn solve<Q: Quest>(part: Part, input: &str) -> Result<Answer, SolveError> {
+    let model = parse_cached::<Q>(input)?;
+    match part {
+        Part::One => Q::part_1(&model),
+        Part::Two => Q::part_2(&model),
+        Part::Three => Q::part_3(&model),
+    }
+}
+
+/// Turns one part of a `Quest` into a `Solver`.
+pub struct QuestSolver<Q> {
+    part: Part,
+    quest: PhantomData<Q>,
+}
+
+impl<Q: Quest> QuestSolver<Q> {
+    pub fn new(part: Part) -> QuestSolver<Q> {
+        QuestSolver {
+            part,
+            quest: PhantomData,
+        }
+    }
+}
+
+impl<Q: Quest> Solver for QuestSolver<Q> {
+    fn solve(&self, input: &str) -> Result<Answer, SolveError> {
+        solve::<Q>(self.part, input)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicUsize;
+    use std::sync::atomic::Ordering;
+
+    use super::*;
+
+    static PARSE_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+    struct Numbers;
+
+    impl Quest for Numbers {
+        type Model = Vec<i64>;
+
+        fn parse(input: &str) -> Result<Vec<i64>, SolveError> {
+            PARSE_COUNT.fetch_add(1, Ordering::SeqCst);
+            input
+                .split(',')
+                .map(|n| {
+                    n.parse()
+                        .map_err(|_| SolveError::at_fragment(input, n, "expected a number"))
+                })
+                .collect()
+        }
+
+        fn part_1(model: &Vec<i64>) -> Result<Answer, SolveError> {
+            Ok(Answer::from(model.iter().sum::<i64>()))
+        }
+
+        fn part_2(model: &Vec<i64>) -> Result<Answer, SolveError> {
+            Ok(Answer::from(model.iter().product::<i64>()))
+        }
+
+        fn part_3(model: &Vec<i64>) -> Result<Answer, SolveError> {
+            model
+                .iter()
+                .max()
+                .map(|&n| Answer::from(n))
+                .ok_or_else(|| SolveError::new("no numbers"))
+        }
+    }
+
+    #[test]
+    fn test_parse_once() {
+        let input = "2,3,4";
+        assert_eq!(
+            Ok(Answer::Int(9)),
+            QuestSolver::<Numbers>::new(Part::One).solve(input)
+        );
+        assert_eq!(
+            Ok(Answer::Int(24)),
+            QuestSolver::<Numbers>::new(Part::Two).solve(input)
+        );
+        assert_eq!(Ok(Answer::Int(4)), solve::<Numbers>(Part::Three, input));
+        assert_eq!(1, PARSE_COUNT.load(Ordering::SeqCst));
+        assert_eq!(Ok(Answer::Int(3)), solve::<Numbers>(Part::One, "1,2"));
+        assert_eq!(2, PARSE_COUNT.load(Ordering::SeqCst));
+        assert_eq!(
+            "line 1, col 3: expected a number",
+            solve::<Numbers>(Part::One, "1,x").unwrap_err().to_string()
+        );
+    }
+}
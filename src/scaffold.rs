@@ -0,0 +1,133 @@
+use std::error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::path::PathBuf;
+
+use crate::types::EventKind;
+use crate::types::ParseError;
+use crate::types::module_name;
+
+/// A solver file for a quest which has not been solved yet.
+const QUEST_TEMPLATE: &str = "\
+use crate::solver::SolveError;
+
+pub fn solve_part_1(_input: &str) -> Result<String, SolveError> {
+    Err(SolveError::new(\"not solved yet\"))
+}
+
+pub fn solve_part_2(_input: &str) -> Result<String, SolveError> {
+    Err(SolveError::new(\"not solved yet\"))
+}
+
+pub fn solve_part_3(_input: &str) -> Result<String, SolveError> {
+    Err(SolveError::new(\"not solved yet\"))
+}
+";
+
+/// Why an event could not be scaffolded.
+#[derive(Debug)]
+pub enum Error {
+    InvalidEvent(ParseError),
+    IoError(io::Error),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Self::InvalidEvent(ref e) => e.fmt(f),
+            Self::IoError(ref e) => write!(f, "failed to write the solver files: {}", e),
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            Self::InvalidEvent(ref e) => Some(e),
+            Self::IoError(ref e) => Some(e),
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Error {
+        Error::IoError(e)
+    }
+}
+
+/// The `mod.rs` of an event, declaring one module per quest of its kind, sorted as
+/// rustfmt would.
+fn event_module(kind: EventKind) -> String {
+    let mut quests: Vec<String> = kind.quests().map(|q| format!("quest{q}")).collect();
+    quests.sort();
+    quests.iter().map(|q| format!("pub mod {q};\n")).collect()
+}
+
+/// Creates the module of an event under the source directory, with a solver file for
+/// every quest of the event which does not have one yet. Existing solvers are left
+/// alone. Returns the files which were written.
+pub fn scaffold_event(src: &Path, event: i16) -> Result<Vec<PathBuf>, Error> {
+    let kind = EventKind::of(event).map_err(Error::InvalidEvent)?;
+    let dir = src.join(module_name(event));
+    fs::create_dir_all(&dir)?;
+    let mut written = vec![];
+    for quest in kind.quests() {
+        let path = dir.join(format!("quest{quest}.rs"));
+        if !path.exists() {
+            fs::write(&path, QUEST_TEMPLATE)?;
+            written.push(path);
+        }
+    }
+    let path = dir.join("mod.rs");
+    let contents = event_module(kind);
+    if fs::read_to_string(&path).ok().as_deref() != Some(contents.as_str()) {
+        fs::write(&path, contents)?;
+        written.push(path);
+    }
+    Ok(written)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::submissionstore::temp_dir;
+
+    #[test]
+    fn test_event_module() {
+        assert_eq!(
+            "pub mod quest1;\npub mod quest2;\npub mod quest3;\n",
+            event_module(EventKind::Story)
+        );
+        let main = event_module(EventKind::Main);
+        assert_eq!(20, main.lines().count());
+        assert!(main.starts_with("pub mod quest1;\npub mod quest10;\n"));
+    }
+
+    #[test]
+    fn test_scaffold_event() {
+        let src = temp_dir("scaffold");
+        fs::create_dir_all(src.join("event3")).unwrap();
+        fs::write(src.join("event3/quest2.rs"), "// solved").unwrap();
+        let written = scaffold_event(&src, 3).unwrap();
+        assert_eq!(
+            vec![
+                src.join("event3/quest1.rs"),
+                src.join("event3/quest3.rs"),
+                src.join("event3/mod.rs")
+            ],
+            written
+        );
+        assert_eq!(
+            "// solved",
+            fs::read_to_string(src.join("event3/quest2.rs")).unwrap()
+        );
+        assert!(scaffold_event(&src, 3).unwrap().is_empty());
+        assert_eq!(20, scaffold_event(&src, 2026).unwrap().len() - 1);
+        assert!(matches!(
+            scaffold_event(&src, 1999),
+            Err(Error::InvalidEvent(ParseError::InvalidEvent(1999)))
+        ));
+    }
+}
@@ -0,0 +1,311 @@
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::collections::HashMap;
+use std::collections::VecDeque;
+use std::hash::Hash;
+use std::ops::Add;
+
+/// The cheapest way found to a goal.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Path<S, C> {
+    pub cost: C,
+    /// The states from a start to the goal, both included.
+    pub states: Vec<S>,
+}
+
+/// The states seen so far, each with the state it was reached from and its cost.
+struct Tree<S, C> {
+    states: Vec<S>,
+    index: HashMap<S, usize>,
+    parents: Vec<Option<usize>>,
+    costs: Vec<C>,
+}
+
+impl<S: Clone + Eq + Hash, C: Copy> Tree<S, C> {
+    fn new() -> Tree<S, C> {
+        Tree {
+            states: Vec::new(),
+            index: HashMap::new(),
+            parents: Vec::new(),
+            costs: Vec::new(),
+        }
+    }
+
+    /// Records that the state is reached at the cost, unless it was reached before.
+    /// Returns its index if it is new.
+    fn insert(&mut self, state: S, parent: Option<usize>, cost: C) -> Option<usize> {
+        if self.index.contains_key(&state) {
+            return None;
+        }
+        let i = self.states.len();
+        self.index.insert(state.clone(), i);
+        self.states.push(state);
+        self.parents.push(parent);
+        self.costs.push(cost);
+        Some(i)
+    }
+
+    fn path(&self, mut i: usize) -> Path<S, C> {
+        let cost = self.costs[i];
+        let mut states = vec![self.states[i].clone()];
+        while let Some(parent) = self.parents[i] {
+            states.push(self.states[parent].clone());
+            i = parent;
+        }
+        states.reverse();
+        Path { cost, states }
+    }
+}
+
+/// Finds a shortest path from any of the starts to a goal, where every step costs 1.
+pub fn bfs<S, I>(
+    starts: impl IntoIterator<Item = S>,
+    mut successors: impl FnMut(&S) -> I,
+    mut is_goal: impl FnMut(&S) -> bool,
+) -> Option<Path<S, usize>>
+where
+    S: Clone + Eq + Hash,
+    I: IntoIterator<Item = S>,
+{
+    let mut tree = Tree::new();
+    let mut queue: VecDeque<_> = starts
+        .into_iter()
+        .filter_map(|start| tree.insert(start, None, 0))
+        .collect();
+    while let Some(i) = queue.pop_front() {
+        if is_goal(&tree.states[i]) {
+            return Some(tree.path(i));
+        }
+        let cost = tree.costs[i] + 1;
+        for next in successors(&tree.states[i]) {
+            queue.extend(tree.insert(next, Some(i), cost));
+        }
+    }
+    None
+}
+
+/// The number of steps from the nearest start to every state reachable from them.
+pub fn distances<S, I>(
+    starts: impl IntoIterator<Item = S>,
+    mut successors: impl FnMut(&S) -> I,
+) -> HashMap<S, usize>
+where
+    S: Clone + Eq + Hash,
+    I: IntoIterator<Item = S>,
+{
+    let mut distances = HashMap::new();
+    let mut queue = VecDeque::new();
+    for start in starts {
+        if !distances.contains_key(&start) {
+            distances.insert(start.clone(), 0);
+            queue.push_back((start, 0));
+        }
+    }
+    while let Some((state, distance)) = queue.pop_front() {
+        for next in successors(&state) {
+            if !distances.contains_key(&next) {
+                distances.insert(next.clone(), distance + 1);
+                queue.push_back((next, distance + 1));
+            }
+        }
+    }
+    distances
+}
+
+/// Finds a cheapest path from any of the starts to a goal. The successors of a state
+/// come with the cost of the step to them, which must not be negative.
+pub fn dijkstra<S, C, I>(
+    starts: impl IntoIterator<Item = S>,
+    successors: impl FnMut(&S) -> I,
+    is_goal: impl FnMut(&S) -> bool,
+) -> Option<Path<S, C>>
+where
+    S: Clone + Eq + Hash,
+    C: Copy + Default + Ord + Add<Output = C>,
+    I: IntoIterator<Item = (S, C)>,
+{
+    astar(starts, successors, |_| C::default(), is_goal)
+}
+
+/// Like `dijkstra`, but explores first the states from which the heuristic expects a
+/// goal to be cheap to reach. The heuristic must never overestimate that cost, or the
+/// path found may not be the cheapest.
+pub fn astar<S, C, I>(
+    starts: impl IntoIterator<Item = S>,
+    mut successors: impl FnMut(&S) -> I,
+    mut heuristic: impl FnMut(&S) -> C,
+    mut is_goal: impl FnMut(&S) -> bool,
+) -> Option<Path<S, C>>
+where
+    S: Clone + Eq + Hash,
+    C: Copy + Default + Ord + Add<Output = C>,
+    I: IntoIterator<Item = (S, C)>,
+{
+    let mut tree = Tree::new();
+    let mut queue = BinaryHeap::new();
+    for start in starts {
+        if let Some(i) = tree.insert(start, None, C::default()) {
+            queue.push(Reverse((heuristic(&tree.states[i]), C::default(), i)));
+        }
+    }
+    while let Some(Reverse((_, cost, i))) = queue.pop() {
+        if cost > tree.costs[i] {
+            // Reached more cheaply since it was queued.
+            continue;
+        }
+        if is_goal(&tree.states[i]) {
+            return Some(tree.path(i));
+        }
+        for (next, step) in successors(&tree.states[i]) {
+            let next_cost = cost + step;
+            let j = match tree.index.get(&next) {
+                Some(&j) if next_cost < tree.costs[j] => {
+                    tree.costs[j] = next_cost;
+                    tree.parents[j] = Some(i);
+                    j
+                }
+                Some(_) => continue,
+                None => tree.insert(next, Some(i), next_cost).unwrap(),
+            };
+            queue.push(Reverse((
+                next_cost + heuristic(&tree.states[j]),
+                next_cost,
+                j,
+            )));
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use itertools::Itertools;
+
+    use super::*;
+    use crate::geometry::Dir4;
+    use crate::geometry::Point;
+    use crate::grid::Grid;
+
+    const MAZE: &str = "\
+S..#....
+.#.#.##.
+.#....E.
+.####.#.
+......#.";
+
+    fn open_neighbours(maze: &Grid<char>, point: Point<usize>) -> Vec<Point<usize>> {
+        maze.neighbours_4(point)
+            .filter(|&next| maze[next] != '#')
+            .collect()
+    }
+
+    #[test]
+    fn test_bfs() {
+        let maze = Grid::parse(MAZE).unwrap();
+        let path = bfs(
+            maze.find(&'S'),
+            |&point| open_neighbours(&maze, point),
+            |&point| maze[point] == 'E',
+        )
+        .unwrap();
+        assert_eq!(8, path.cost);
+        assert_eq!(9, path.states.len());
+        assert_eq!(Point::new(0, 0), path.states[0]);
+        assert_eq!(Some(&Point::new(6, 2)), path.states.last());
+        for (from, to) in path.states.iter().tuple_windows() {
+            assert_eq!(1, from.manhattan(*to));
+        }
+    }
+
+    #[test]
+    fn test_bfs_from_several_starts() {
+        let maze = Grid::parse(MAZE).unwrap();
+        let path = bfs(
+            [Point::new(0, 0), Point::new(5, 4)],
+            |&point| open_neighbours(&maze, point),
+            |&point| maze[point] == 'E',
+        )
+        .unwrap();
+        assert_eq!(3, path.cost);
+        assert_eq!(
+            vec![
+                Point::new(5, 4),
+                Point::new(5, 3),
+                Point::new(5, 2),
+                Point::new(6, 2)
+            ],
+            path.states
+        );
+        assert_eq!(
+            None,
+            bfs(
+                [Point::new(0, 0)],
+                |&point| open_neighbours(&maze, point),
+                |_| false
+            )
+        );
+    }
+
+    #[test]
+    fn test_distances() {
+        let maze = Grid::parse(MAZE).unwrap();
+        let distances = distances([Point::new(0, 0), Point::new(7, 4)], |&point| {
+            open_neighbours(&maze, point)
+        });
+        assert_eq!(Some(&0), distances.get(&Point::new(7, 4)));
+        assert_eq!(Some(&3), distances.get(&Point::new(2, 1)));
+        assert_eq!(Some(&3), distances.get(&Point::new(6, 2)));
+        assert_eq!(None, distances.get(&Point::new(3, 0)));
+        assert_eq!(maze.find_all(&'#').count() + distances.len(), 40);
+    }
+
+    #[test]
+    fn test_dijkstra() {
+        // Going along the edge of the grid costs more than cutting through the middle.
+        let costs = Grid::parse_digits("1999\n1119\n9911\n9991").unwrap();
+        let path = dijkstra(
+            [Point::new(0, 0)],
+            |&point| {
+                costs
+                    .neighbours_4(point)
+                    .map(|next| (next, costs[next] as u32))
+                    .collect::<Vec<_>>()
+            },
+            |&point| point == Point::new(3, 3),
+        )
+        .unwrap();
+        assert_eq!(6, path.cost);
+        assert_eq!(
+            vec![
+                Point::new(0, 0),
+                Point::new(0, 1),
+                Point::new(1, 1),
+                Point::new(2, 1),
+                Point::new(2, 2),
+                Point::new(3, 2),
+                Point::new(3, 3)
+            ],
+            path.states
+        );
+    }
+
+    #[test]
+    fn test_astar() {
+        let goal = Point::new(7i32, -3);
+        let mut expanded = 0;
+        let path = astar(
+            [Point::new(0, 0)],
+            |&point| {
+                expanded += 1;
+                Dir4::ALL.map(|dir| (point + dir.delta(), 1))
+            },
+            |&point| point.manhattan(goal),
+            |&point| point == goal,
+        )
+        .unwrap();
+        assert_eq!(10, path.cost);
+        // The heuristic leads straight to the goal on an open plane, where a
+        // breadth-first search would explore hundreds of points.
+        assert!(expanded < 50, "expanded {expanded} states");
+    }
+}
@@ -0,0 +1,405 @@
+use std::collections::BTreeMap;
+use std::error;
+use std::fmt;
+use std::marker::PhantomData;
+use std::str::FromStr;
+
+use num::BigInt;
+
+/// A solution to a puzzle part, formatted the way the server expects it.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Answer {
+    Int(i64),
+    BigInt(BigInt),
+    Text(String),
+    /// Several values separated by spaces, e.g. `13 43`.
+    Multi(Vec<Answer>),
+}
+
+impl fmt::Display for Answer {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Self::Int(n) => write!(f, "{n}"),
+            Self::BigInt(ref n) => write!(f, "{n}"),
+            Self::Text(ref s) => write!(f, "{s}"),
+            Self::Multi(ref values) => {
+                for (i, value) in values.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, " ")?;
+                    }
+                    write!(f, "{value}")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl From<i64> for Answer {
+    fn from(n: i64) -> Answer {
+        Answer::Int(n)
+    }
+}
+
+impl From<i32> for Answer {
+    fn from(n: i32) -> Answer {
+        Answer::Int(n.into())
+    }
+}
+
+impl From<u64> for Answer {
+    fn from(n: u64) -> Answer {
+        match i64::try_from(n) {
+            Ok(n) => Answer::Int(n),
+            Err(_) => Answer::BigInt(n.into()),
+        }
+    }
+}
+
+impl From<usize> for Answer {
+    fn from(n: usize) -> Answer {
+        Answer::from(n as u64)
+    }
+}
+
+impl From<BigInt> for Answer {
+    fn from(n: BigInt) -> Answer {
+        Answer::BigInt(n)
+    }
+}
+
+impl From<String> for Answer {
+    fn from(s: String) -> Answer {
+        Answer::Text(s)
+    }
+}
+
+impl From<&str> for Answer {
+    fn from(s: &str) -> Answer {
+        Answer::Text(s.to_string())
+    }
+}
+
+/// Where in the input a problem was found, counted from 1.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Position {
+    pub line: usize,
+    pub column: usize,
+}
+
+/// Why a solver could not produce an answer.
+#[derive(Debug, PartialEq)]
+pub struct SolveError {
+    pub message: String,
+    pub position: Option<Position>,
+}
+
+impl SolveError {
+    pub fn new(message: impl Into<String>) -> SolveError {
+        SolveError {
+            message: message.into(),
+            position: None,
+        }
+    }
+
+    pub fn at(line: usize, column: usize, message: impl Into<String>) -> SolveError {
+        SolveError {
+            message: message.into(),
+            position: Some(Position { line, column }),
+        }
+    }
+
+    /// Reports a problem with `fragment`, which must be a slice of `input`, at its
+    /// position in the input.
+    pub fn at_fragment(input: &str, fragment: &str, message: impl Into<String>) -> SolveError {
+        let offset = (fragment.as_ptr() as usize).wrapping_sub(input.as_ptr() as usize);
+        if offset > input.len() {
+            return SolveError::new(message);
+        }
+        let before = &input[..offset];
+        let line = before.matches('\n').count() + 1;
+        let column = before.len() - before.rfind('\n').map_or(0, |i| i + 1) + 1;
+        SolveError::at(line, column, message)
+    }
+}
+
+impl fmt::Display for SolveError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.position {
+            Some(Position { line, column }) => {
+                write!(f, "line {line}, col {column}: {}", self.message)
+            }
+            None => write!(f, "{}", self.message),
+        }
+    }
+}
+
+impl error::Error for SolveError {}
+
+/// A named puzzle constant a solver lets the user override, e.g. to run the examples
+/// which use different constants than the real puzzle. The default is kept as text
+/// and parsed when read, like the overrides given on the command line.
+pub struct Param<T> {
+    pub name: &'static str,
+    pub default: &'static str,
+    value_type: PhantomData<T>,
+}
+
+impl<T> Param<T> {
+    pub const fn new(name: &'static str, default: &'static str) -> Param<T> {
+        Param {
+            name,
+            default,
+            value_type: PhantomData,
+        }
+    }
+}
+
+/// Parameter values overriding the solvers' defaults, by name.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Params {
+    values: BTreeMap<String, String>,
+}
+
+impl Params {
+    pub fn get<T: FromStr>(&self, param: &Param<T>) -> Result<T, SolveError> {
+        let value = self
+            .values
+            .get(param.name)
+            .map_or(param.default, |v| v.as_str());
+        value
+            .parse()
+            .map_err(|_| SolveError::new(format!("invalid value {value:?} for {}", param.name)))
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.values.keys().map(|k| k.as_str())
+    }
+}
+
+impl<'a> FromIterator<(&'a str, &'a str)> for Params {
+    fn from_iter<I: IntoIterator<Item = (&'a str, &'a str)>>(iter: I) -> Params {
+        Params {
+            values: iter
+                .into_iter()
+                .map(|(name, value)| (name.to_string(), value.to_string()))
+                .collect(),
+        }
+    }
+}
+
+/// Parses a `name=value` parameter assignment.
+pub fn parse_param_assignment(s: &str) -> Result<(String, String), String> {
+    match s.split_once('=') {
+        Some((name, value)) if !name.is_empty() => Ok((name.to_string(), value.to_string())),
+        _ => Err(format!("expected name=value, got {s:?}")),
+    }
+}
+
+/// Solves one puzzle part.
+pub trait Solver {
+    fn solve(&self, input: &str) -> Result<Answer, SolveError>;
+
+    /// The names of the parameters the solver accepts.
+    fn param_names(&self) -> &[&'static str] {
+        &[]
+    }
+
+    /// Plausible answers ranked from the most to the least likely, for puzzles where
+    /// the solver cannot tell which one is right.
+    fn solve_candidates(&self, input: &str) -> Result<Vec<Answer>, SolveError> {
+        self.solve(input).map(|answer| vec![answer])
+    }
+}
+
+/// What solver functions may return: the legacy answer string or list of candidate
+/// strings, or a typed answer.
+pub trait IntoSolution {
+    fn into_solution(self) -> Result<Vec<Answer>, SolveError>;
+}
+
+impl IntoSolution for String {
+    fn into_solution(self) -> Result<Vec<Answer>, SolveError> {
+        Ok(vec![Answer::Text(self)])
+    }
+}
+
+impl IntoSolution for Vec<String> {
+    fn into_solution(self) -> Result<Vec<Answer>, SolveError> {
+        Ok(self.into_iter().map(Answer::Text).collect())
+    }
+}
+
+impl IntoSolution for Answer {
+    fn into_solution(self) -> Result<Vec<Answer>, SolveError> {
+        Ok(vec![self])
+    }
+}
+
+impl IntoSolution for Result<Answer, SolveError> {
+    fn into_solution(self) -> Result<Vec<Answer>, SolveError> {
+        self.map(|answer| vec![answer])
+    }
+}
+
+impl IntoSolution for Result<String, SolveError> {
+    fn into_solution(self) -> Result<Vec<Answer>, SolveError> {
+        self.map(|answer| vec![Answer::Text(answer)])
+    }
+}
+
+/// Turns a solver function, legacy or typed, into a `Solver`.
+pub struct FnSolver<F>(pub F);
+
+impl<F, R> Solver for FnSolver<F>
+where
+    F: Fn(&str) -> R,
+    R: IntoSolution,
+{
+    fn solve(&self, input: &str) -> Result<Answer, SolveError> {
+        self.solve_candidates(input)?
+            .into_iter()
+            .next()
+            .ok_or_else(|| SolveError::new("the solver found no answer"))
+    }
+
+    fn solve_candidates(&self, input: &str) -> Result<Vec<Answer>, SolveError> {
+        (self.0)(input).into_solution()
+    }
+}
+
+/// Turns a solver function taking named parameters into a `Solver`, bound to the
+/// given parameter values.
+pub struct ParamSolver<F> {
+    solve_fn: F,
+    names: &'static [&'static str],
+    params: Params,
+}
+
+impl<F> ParamSolver<F> {
+    pub fn new(solve_fn: F, names: &'static [&'static str], params: Params) -> ParamSolver<F> {
+        ParamSolver {
+            solve_fn,
+            names,
+            params,
+        }
+    }
+}
+
+impl<F, R> Solver for ParamSolver<F>
+where
+    F: Fn(&str, &Params) -> R,
+    R: IntoSolution,
+{
+    fn solve(&self, input: &str) -> Result<Answer, SolveError> {
+        self.solve_candidates(input)?
+            .into_iter()
+            .next()
+            .ok_or_else(|| SolveError::new("the solver found no answer"))
+    }
+
+    fn param_names(&self) -> &[&'static str] {
+        self.names
+    }
+
+    fn solve_candidates(&self, input: &str) -> Result<Vec<Answer>, SolveError> {
+        (self.solve_fn)(input, &self.params).into_solution()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_answer_format() {
+        assert_eq!("-42", Answer::from(-42).to_string());
+        assert_eq!("18446744073709551615", Answer::from(u64::MAX).to_string());
+        assert!(matches!(Answer::from(u64::MAX), Answer::BigInt(_)));
+        assert_eq!("abc", Answer::from("abc").to_string());
+        assert_eq!(
+            "13 43",
+            Answer::Multi(vec![Answer::from(13), Answer::from(43)]).to_string()
+        );
+    }
+
+    #[test]
+    fn test_error_position() {
+        let input = "12\n34\n5x6";
+        let error = SolveError::at_fragment(input, &input[7..8], "not a digit");
+        assert_eq!(Some(Position { line: 3, column: 2 }), error.position);
+        assert_eq!("line 3, col 2: not a digit", error.to_string());
+        let error = SolveError::at_fragment(input, "elsewhere", "oops");
+        assert_eq!(None, error.position);
+        assert_eq!("oops", error.to_string());
+    }
+
+    fn legacy(input: &str) -> String {
+        input.len().to_string()
+    }
+
+    fn typed(input: &str) -> Result<Answer, SolveError> {
+        input
+            .parse::<i64>()
+            .map(Answer::Int)
+            .map_err(|e| SolveError::at(1, 1, e.to_string()))
+    }
+
+    fn ranked(_: &str) -> Vec<String> {
+        vec![String::from("1"), String::from("2")]
+    }
+
+    #[test]
+    fn test_fn_solvers() {
+        assert_eq!(Ok(Answer::from("3")), FnSolver(legacy).solve("abc"));
+        assert_eq!(Ok(Answer::Int(7)), FnSolver(typed).solve("7"));
+        assert!(FnSolver(typed).solve("x").unwrap_err().position.is_some());
+        assert_eq!(
+            Ok(vec![Answer::from("1"), Answer::from("2")]),
+            FnSolver(ranked).solve_candidates("")
+        );
+        assert_eq!(Ok(Answer::from("1")), FnSolver(ranked).solve(""));
+        let empty = |_: &str| Vec::<String>::new();
+        assert!(FnSolver(empty).solve("").is_err());
+    }
+
+    const TURNS: Param<usize> = Param::new("turns", "20");
+
+    fn with_turns(input: &str, params: &Params) -> Result<String, SolveError> {
+        Ok(input.repeat(params.get(&TURNS)?))
+    }
+
+    #[test]
+    fn test_params() {
+        assert_eq!(Ok(20), Params::default().get(&TURNS));
+        let params = Params::from_iter([("turns", "3")]);
+        assert_eq!(Ok(3), params.get(&TURNS));
+        assert_eq!(vec!["turns"], params.names().collect::<Vec<_>>());
+        assert_eq!(
+            "invalid value \"x\" for turns",
+            Params::from_iter([("turns", "x")])
+                .get(&TURNS)
+                .unwrap_err()
+                .to_string()
+        );
+        assert_eq!(
+            Ok((String::from("track"), String::from("+=-S"))),
+            parse_param_assignment("track=+=-S")
+        );
+        assert!(parse_param_assignment("turns").is_err());
+        assert!(parse_param_assignment("=3").is_err());
+    }
+
+    #[test]
+    fn test_param_solver() {
+        let solver = ParamSolver::new(
+            with_turns,
+            &[TURNS.name],
+            Params::from_iter([("turns", "2")]),
+        );
+        assert_eq!(&["turns"], solver.param_names());
+        assert_eq!(Ok(Answer::from("abab")), solver.solve("ab"));
+        let solver = ParamSolver::new(with_turns, &[TURNS.name], Params::default());
+        assert_eq!(20, solver.solve("a").unwrap().to_string().len());
+    }
+}
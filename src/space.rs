@@ -0,0 +1,178 @@
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::hash::Hash;
+use std::ops::Add;
+use std::ops::Sub;
+
+use crate::search;
+
+/// A point in space, or the offset between two points. `z` grows upwards.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct Point3<T> {
+    pub x: T,
+    pub y: T,
+    pub z: T,
+}
+
+impl<T> Point3<T> {
+    pub const fn new(x: T, y: T, z: T) -> Point3<T> {
+        Point3 { x, y, z }
+    }
+}
+
+impl<T: Add<Output = T>> Add for Point3<T> {
+    type Output = Point3<T>;
+
+    fn add(self, other: Point3<T>) -> Point3<T> {
+        Point3::new(self.x + other.x, self.y + other.y, self.z + other.z)
+    }
+}
+
+impl<T: Sub<Output = T>> Sub for Point3<T> {
+    type Output = Point3<T>;
+
+    fn sub(self, other: Point3<T>) -> Point3<T> {
+        Point3::new(self.x - other.x, self.y - other.y, self.z - other.z)
+    }
+}
+
+impl<T: Copy + From<i8> + Add<Output = T>> Point3<T> {
+    /// The points sharing a face with this one.
+    pub fn neighbours_6(self) -> impl Iterator<Item = Point3<T>> {
+        Dir6::ALL.into_iter().map(move |dir| self + dir.delta())
+    }
+}
+
+/// One of the six directions along the axes.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Dir6 {
+    Up,
+    Down,
+    Right,
+    Left,
+    Forward,
+    Back,
+}
+
+impl Dir6 {
+    pub const ALL: [Dir6; 6] = [
+        Dir6::Up,
+        Dir6::Down,
+        Dir6::Right,
+        Dir6::Left,
+        Dir6::Forward,
+        Dir6::Back,
+    ];
+
+    /// The direction named by one of `U`, `D`, `R`, `L`, `F` and `B`.
+    pub fn from_letter(letter: char) -> Option<Dir6> {
+        match letter {
+            'U' => Some(Dir6::Up),
+            'D' => Some(Dir6::Down),
+            'R' => Some(Dir6::Right),
+            'L' => Some(Dir6::Left),
+            'F' => Some(Dir6::Forward),
+            'B' => Some(Dir6::Back),
+            _ => None,
+        }
+    }
+
+    /// The offset of one step in the direction.
+    pub fn delta<T: From<i8>>(self) -> Point3<T> {
+        let (x, y, z) = match self {
+            Dir6::Up => (0, 0, 1),
+            Dir6::Down => (0, 0, -1),
+            Dir6::Right => (1, 0, 0),
+            Dir6::Left => (-1, 0, 0),
+            Dir6::Forward => (0, 1, 0),
+            Dir6::Back => (0, -1, 0),
+        };
+        Point3::new(T::from(x), T::from(y), T::from(z))
+    }
+}
+
+/// The points passed through, one step at a time, when making the moves from `start`,
+/// which is not included.
+pub fn trace<T>(start: Point3<T>, moves: impl IntoIterator<Item = (Dir6, usize)>) -> Vec<Point3<T>>
+where
+    T: Copy + From<i8> + Add<Output = T>,
+{
+    let mut point = start;
+    let mut path = vec![];
+    for (dir, steps) in moves {
+        for _ in 0..steps {
+            point = point + dir.delta();
+            path.push(point);
+        }
+    }
+    path
+}
+
+/// The number of steps from the nearest start to every voxel reachable from it,
+/// moving through the voxels only.
+pub fn distances<T>(
+    starts: impl IntoIterator<Item = Point3<T>>,
+    voxels: &HashSet<Point3<T>>,
+) -> HashMap<Point3<T>, usize>
+where
+    T: Copy + Eq + Hash + From<i8> + Add<Output = T>,
+{
+    search::distances(starts, |point| {
+        point
+            .neighbours_6()
+            .filter(|next| voxels.contains(next))
+            .collect::<Vec<_>>()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_directions() {
+        assert_eq!(
+            Point3::<i32>::default(),
+            Dir6::ALL
+                .into_iter()
+                .fold(Point3::default(), |sum, dir| sum + dir.delta())
+        );
+        assert_eq!(Some(Dir6::Forward), Dir6::from_letter('F'));
+        assert_eq!(None, Dir6::from_letter('N'));
+        let neighbours: HashSet<_> = Point3::new(1i64, 2, 3).neighbours_6().collect();
+        assert_eq!(6, neighbours.len());
+        assert!(neighbours.contains(&Point3::new(1, 1, 3)));
+        assert!(neighbours.contains(&Point3::new(1, 2, 4)));
+    }
+
+    #[test]
+    fn test_trace() {
+        let path = trace(
+            Point3::new(0i64, 0, 0),
+            [(Dir6::Up, 2), (Dir6::Right, 1), (Dir6::Back, 1)],
+        );
+        assert_eq!(
+            vec![
+                Point3::new(0, 0, 1),
+                Point3::new(0, 0, 2),
+                Point3::new(1, 0, 2),
+                Point3::new(1, -1, 2),
+            ],
+            path
+        );
+    }
+
+    #[test]
+    fn test_distances() {
+        // An L shape with a detached voxel.
+        let mut voxels: HashSet<_> = trace(Point3::default(), [(Dir6::Up, 3), (Dir6::Left, 2)])
+            .into_iter()
+            .collect();
+        voxels.insert(Point3::new(5i64, 5, 5));
+        let distances = distances([Point3::new(0, 0, 1)], &voxels);
+        assert_eq!(5, distances.len());
+        assert_eq!(Some(&0), distances.get(&Point3::new(0, 0, 1)));
+        assert_eq!(Some(&4), distances.get(&Point3::new(-2, 0, 3)));
+        assert_eq!(None, distances.get(&Point3::new(5, 5, 5)));
+    }
+}
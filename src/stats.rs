@@ -0,0 +1,440 @@
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::time::Duration;
+use std::time::SystemTime;
+
+use pretty_duration::pretty_duration;
+
+use crate::autosubmit::parse_puzzle_key_string;
+use crate::submissionstore::Log;
+
+/// What the submission log knows about one part of a quest.
+#[derive(Debug, PartialEq)]
+pub struct PartStats {
+    pub user_id: i64,
+    pub event: i16,
+    pub quest: i8,
+    pub part: u8,
+    pub accepted_answer: Option<String>,
+    pub wrong_attempts: usize,
+    pub first_character_hints: usize,
+    pub length_hints: usize,
+    pub solved_at: Option<SystemTime>,
+    pub local_time: Option<Duration>,
+    pub global_time: Option<Duration>,
+    pub rank: Option<i32>,
+}
+
+/// Sums over all the parts of an event solved by one user.
+#[derive(Debug, PartialEq)]
+pub struct EventTotals {
+    pub user_id: i64,
+    pub event: i16,
+    pub parts_solved: usize,
+    pub wrong_attempts: usize,
+    pub hints: usize,
+    pub local_time: Duration,
+}
+
+/// Extracts one row per puzzle part from the log, sorted by user, event, quest and
+/// part. Only the given user is kept if one is specified. Entries written before the
+/// log was keyed by user are skipped.
+pub fn collect(log: &Log, user_id: Option<i64>) -> Vec<PartStats> {
+    // Submitting only migrates entries without a user while there are no others.
+    let has_user_keys = log
+        .answers
+        .keys()
+        .any(|key| parse_puzzle_key_string(key).is_some());
+    let mut stats = vec![];
+    for (key, entry) in &log.answers {
+        let Some((entry_user_id, puzzle_key)) = parse_puzzle_key_string(key) else {
+            if has_user_keys {
+                log::warn!("skipping {key}, it predates the keys by user");
+            } else {
+                log::warn!(
+                    "skipping {key}, it predates the keys by user, submit an answer to migrate the log"
+                );
+            }
+            continue;
+        };
+        if user_id.is_some_and(|u| u != entry_user_id) {
+            continue;
+        }
+        let accepted = entry.attempts.iter().find(|a| a.response.correct);
+        stats.push(PartStats {
+            user_id: entry_user_id,
+            event: puzzle_key.event,
+            quest: puzzle_key.quest,
+            part: puzzle_key.part.as_u8(),
+            accepted_answer: entry.accepted_answer.clone(),
+            wrong_attempts: entry.rejected_answers.len(),
+            first_character_hints: entry.rejected_first_characters.len(),
+            length_hints: entry.rejected_answer_lengths.len(),
+            solved_at: accepted.map(|a| a.response.time),
+            local_time: accepted.map(|a| a.response.local_time),
+            global_time: accepted.map(|a| a.response.global_time),
+            rank: accepted.map(|a| a.response.global_place),
+        });
+    }
+    stats.sort_by_key(|s| (s.user_id, s.event, s.quest, s.part));
+    stats
+}
+
+/// Totals per user and event, in the order of the users and then the events.
+pub fn event_totals(stats: &[PartStats]) -> Vec<EventTotals> {
+    let mut totals: BTreeMap<(i64, i16), EventTotals> = BTreeMap::new();
+    for s in stats {
+        let total = totals.entry((s.user_id, s.event)).or_insert(EventTotals {
+            user_id: s.user_id,
+            event: s.event,
+            parts_solved: 0,
+            wrong_attempts: 0,
+            hints: 0,
+            local_time: Duration::ZERO,
+        });
+        if s.accepted_answer.is_some() {
+            total.parts_solved += 1;
+        }
+        total.wrong_attempts += s.wrong_attempts;
+        total.hints += s.first_character_hints + s.length_hints;
+        total.local_time += s.local_time.unwrap_or_default();
+    }
+    totals.into_values().collect()
+}
+
+/// Time from the quest opening to its last solved part, per (user, event, quest).
+fn quest_solve_times(stats: &[PartStats]) -> Vec<((i64, i16, i8), Duration)> {
+    let mut times: BTreeMap<(i64, i16, i8), Duration> = BTreeMap::new();
+    for s in stats {
+        if let Some(local_time) = s.local_time {
+            let time = times.entry((s.user_id, s.event, s.quest)).or_default();
+            *time = (*time).max(local_time);
+        }
+    }
+    times.into_iter().collect()
+}
+
+/// Quotes a CSV field if it contains anything that would break the row.
+pub fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+pub fn optional<T: ToString>(value: Option<T>) -> String {
+    value.map(|v| v.to_string()).unwrap_or_default()
+}
+
+/// One row per part, with the times in milliseconds so that they are easy to
+/// process further.
+pub fn to_csv(stats: &[PartStats]) -> String {
+    let mut csv = String::from(
+        "user_id,event,quest,part,accepted_answer,wrong_attempts,first_character_hints,\
+         length_hints,local_time_ms,global_time_ms,rank\n",
+    );
+    for s in stats {
+        writeln!(
+            csv,
+            "{},{},{},{},{},{},{},{},{},{},{}",
+            s.user_id,
+            s.event,
+            s.quest,
+            s.part,
+            csv_field(s.accepted_answer.as_deref().unwrap_or_default()),
+            s.wrong_attempts,
+            s.first_character_hints,
+            s.length_hints,
+            optional(s.local_time.map(|t| t.as_millis())),
+            optional(s.global_time.map(|t| t.as_millis())),
+            optional(s.rank),
+        )
+        .unwrap();
+    }
+    csv
+}
+
+pub fn markdown_duration(duration: Option<Duration>) -> String {
+    duration
+        .map(|d| pretty_duration(&d, None))
+        .unwrap_or_else(|| String::from("-"))
+}
+
+/// A table of the parts, followed by a table of the event totals.
+pub fn to_markdown(stats: &[PartStats]) -> String {
+    let mut md = String::from(
+        "| user | event | quest | part | answer | wrong attempts | first character hints | \
+         length hints | local time | global time | rank |\n\
+         |---|---|---|---|---|---|---|---|---|---|---|\n",
+    );
+    for s in stats {
+        writeln!(
+            md,
+            "| {} | {} | {} | {} | {} | {} | {} | {} | {} | {} | {} |",
+            s.user_id,
+            s.event,
+            s.quest,
+            s.part,
+            s.accepted_answer
+                .as_deref()
+                .map(|a| format!("`{}`", a.replace('|', "\\|")))
+                .unwrap_or_else(|| String::from("-")),
+            s.wrong_attempts,
+            s.first_character_hints,
+            s.length_hints,
+            markdown_duration(s.local_time),
+            markdown_duration(s.global_time),
+            s.rank
+                .map(|r| r.to_string())
+                .unwrap_or_else(|| String::from("-")),
+        )
+        .unwrap();
+    }
+    md.push_str(
+        "\n| user | event | parts solved | wrong attempts | hints | total local time |\n\
+         |---|---|---|---|---|---|\n",
+    );
+    for t in event_totals(stats) {
+        writeln!(
+            md,
+            "| {} | {} | {} | {} | {} | {} |",
+            t.user_id,
+            t.event,
+            t.parts_solved,
+            t.wrong_attempts,
+            t.hints,
+            pretty_duration(&t.local_time, None)
+        )
+        .unwrap();
+    }
+    md
+}
+
+const BAR_HEIGHT: usize = 20;
+const BAR_GAP: usize = 4;
+const LABEL_WIDTH: usize = 120;
+const CHART_WIDTH: usize = 600;
+
+/// A horizontal bar chart of the time from each quest opening to its last solved
+/// part, scaled to the slowest quest. The bars are labelled with the user too if the
+/// statistics cover several users.
+pub fn to_svg(stats: &[PartStats]) -> String {
+    let times = quest_solve_times(stats);
+    let several_users = times
+        .iter()
+        .any(|((user_id, _, _), _)| *user_id != times[0].0.0);
+    let longest = times
+        .iter()
+        .map(|(_, time)| time.as_secs_f64())
+        .fold(0.0, f64::max);
+    let height = times.len() * (BAR_HEIGHT + BAR_GAP) + BAR_GAP;
+    let width = LABEL_WIDTH + CHART_WIDTH + LABEL_WIDTH;
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{width}\" height=\"{height}\" \
+         font-family=\"sans-serif\" font-size=\"12\">\n"
+    );
+    for (i, ((user_id, event, quest), time)) in times.iter().enumerate() {
+        let y = BAR_GAP + i * (BAR_HEIGHT + BAR_GAP);
+        let text_y = y + BAR_HEIGHT / 2 + 4;
+        let bar_width = if longest > 0.0 {
+            (time.as_secs_f64() / longest * CHART_WIDTH as f64).round() as usize
+        } else {
+            0
+        };
+        let label = if several_users {
+            format!("user {user_id}: {event} quest {quest}")
+        } else {
+            format!("{event} quest {quest}")
+        };
+        writeln!(
+            svg,
+            "  <text x=\"{}\" y=\"{text_y}\" text-anchor=\"end\">{label}</text>",
+            LABEL_WIDTH - BAR_GAP
+        )
+        .unwrap();
+        writeln!(
+            svg,
+            "  <rect x=\"{LABEL_WIDTH}\" y=\"{y}\" width=\"{bar_width}\" height=\"{BAR_HEIGHT}\" \
+             fill=\"steelblue\"/>"
+        )
+        .unwrap();
+        writeln!(
+            svg,
+            "  <text x=\"{}\" y=\"{text_y}\">{}</text>",
+            LABEL_WIDTH + bar_width + BAR_GAP,
+            pretty_duration(time, None)
+        )
+        .unwrap();
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ecclient::AnswerResponse;
+    use crate::submissionstore::PuzzleLogEntry;
+    use crate::submissionstore::SubmissionAttempt;
+
+    fn attempt(answer: &str, correct: bool, local_time_s: u64) -> SubmissionAttempt {
+        SubmissionAttempt {
+            answer: String::from(answer),
+            submitted_at: SystemTime::UNIX_EPOCH,
+            solver_build: String::from("abc1234"),
+            solve_duration: None,
+            response: AnswerResponse {
+                correct,
+                length_correct: correct,
+                first_correct: correct,
+                time: SystemTime::UNIX_EPOCH,
+                local_time: Duration::from_secs(local_time_s),
+                global_time: Duration::from_secs(local_time_s + 86400),
+                global_place: if correct { 42 } else { 0 },
+                global_score: 0,
+            },
+        }
+    }
+
+    fn sample_log() -> Log {
+        let mut log = Log::default();
+        log.answers.insert(
+            String::from("user1/event2024quest5part2"),
+            PuzzleLogEntry {
+                rejected_answers: vec![String::from("1,2")],
+                accepted_answer: Some(String::from("1,3")),
+                rejected_answer_lengths: vec![3],
+                attempts: vec![attempt("1,2", false, 60), attempt("1,3", true, 120)],
+                ..Default::default()
+            },
+        );
+        log.answers.insert(
+            String::from("user1/event2024quest5part1"),
+            PuzzleLogEntry {
+                accepted_answer: Some(String::from("7")),
+                attempts: vec![attempt("7", true, 30)],
+                ..Default::default()
+            },
+        );
+        log.answers.insert(
+            String::from("user1/event2025quest1part1"),
+            PuzzleLogEntry {
+                rejected_answers: vec![String::from("x")],
+                rejected_first_characters: vec![String::from("x")],
+                ..Default::default()
+            },
+        );
+        log.answers.insert(
+            String::from("user2/event2024quest5part1"),
+            PuzzleLogEntry {
+                accepted_answer: Some(String::from("7")),
+                ..Default::default()
+            },
+        );
+        log.answers.insert(
+            String::from("event2024quest1part1"),
+            PuzzleLogEntry::default(),
+        );
+        log
+    }
+
+    #[test]
+    fn test_collect() {
+        let stats = collect(&sample_log(), Some(1));
+        assert_eq!(3, stats.len());
+        assert_eq!(
+            (2024, 5, 1),
+            (stats[0].event, stats[0].quest, stats[0].part)
+        );
+        assert_eq!(
+            PartStats {
+                user_id: 1,
+                event: 2024,
+                quest: 5,
+                part: 2,
+                accepted_answer: Some(String::from("1,3")),
+                wrong_attempts: 1,
+                first_character_hints: 0,
+                length_hints: 1,
+                solved_at: Some(SystemTime::UNIX_EPOCH),
+                local_time: Some(Duration::from_secs(120)),
+                global_time: Some(Duration::from_secs(86520)),
+                rank: Some(42),
+            },
+            stats[1]
+        );
+        assert_eq!(None, stats[2].local_time);
+        assert_eq!(4, collect(&sample_log(), None).len());
+    }
+
+    #[test]
+    fn test_event_totals() {
+        let totals = event_totals(&collect(&sample_log(), Some(1)));
+        assert_eq!(
+            vec![
+                EventTotals {
+                    user_id: 1,
+                    event: 2024,
+                    parts_solved: 2,
+                    wrong_attempts: 1,
+                    hints: 1,
+                    local_time: Duration::from_secs(150),
+                },
+                EventTotals {
+                    user_id: 1,
+                    event: 2025,
+                    parts_solved: 0,
+                    wrong_attempts: 1,
+                    hints: 1,
+                    local_time: Duration::ZERO,
+                }
+            ],
+            totals
+        );
+    }
+
+    #[test]
+    fn test_to_csv() {
+        let csv = to_csv(&collect(&sample_log(), Some(1)));
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(4, lines.len());
+        assert_eq!("1,2024,5,2,\"1,3\",1,0,1,120000,86520000,42", lines[2]);
+        assert_eq!("1,2025,1,1,,1,1,0,,,", lines[3]);
+    }
+
+    #[test]
+    fn test_to_markdown() {
+        let md = to_markdown(&collect(&sample_log(), Some(1)));
+        assert!(md.contains("| 1 | 2024 | 5 | 2 | `1,3` | 1 | 0 | 1 |"));
+        assert!(md.contains("| 1 | 2025 | 1 | 1 | - | 1 | 1 | 0 | - | - | - |"));
+        assert!(md.contains("| 1 | 2024 | 2 | 1 | 1 |"));
+    }
+
+    #[test]
+    fn test_several_users_are_kept_apart() {
+        let stats = collect(&sample_log(), None);
+        let totals = event_totals(&stats);
+        assert_eq!(
+            vec![(1, 2024, 2, 1), (1, 2025, 0, 1), (2, 2024, 1, 0)],
+            totals
+                .iter()
+                .map(|t| (t.user_id, t.event, t.parts_solved, t.wrong_attempts))
+                .collect::<Vec<_>>()
+        );
+        let md = to_markdown(&stats);
+        assert!(md.contains("| 1 | 2024 | 5 | 1 | `7` |"));
+        assert!(md.contains("| 2 | 2024 | 5 | 1 | `7` |"));
+        assert!(md.contains("| 2 | 2024 | 1 | 0 | 0 |"));
+    }
+
+    #[test]
+    fn test_to_svg() {
+        let svg = to_svg(&collect(&sample_log(), Some(1)));
+        assert!(svg.starts_with("<svg"));
+        assert!(svg.trim_end().ends_with("</svg>"));
+        // Only quest 5 of 2024 was solved, so it gets the full width.
+        assert_eq!(1, svg.matches("<rect").count());
+        assert!(svg.contains(&format!("width=\"{CHART_WIDTH}\"")));
+    }
+}
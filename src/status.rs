@@ -0,0 +1,116 @@
+use std::collections::BTreeSet;
+use std::collections::HashSet;
+use std::fmt::Write;
+
+use crate::types::EventKind;
+use crate::types::Part;
+use crate::types::PuzzleKey;
+use crate::types::Selector;
+
+/// The parts among all three for which the predicate holds, e.g. `1 2 -`.
+fn parts(mut predicate: impl FnMut(Part) -> bool) -> String {
+    Part::ALL
+        .map(|part| {
+            if predicate(part) {
+                part.to_string()
+            } else {
+                String::from("-")
+            }
+        })
+        .join(" ")
+}
+
+/// Which of the selected puzzles have a solver and which are solved, with a summary
+/// line per event followed by a line per quest. Every quest of the event is listed,
+/// as many as its kind has, so that the missing ones stand out.
+pub fn report(
+    events: impl IntoIterator<Item = i16>,
+    selector: Option<&Selector>,
+    has_solver: impl Fn(&PuzzleKey) -> bool,
+    solved: &HashSet<PuzzleKey>,
+) -> String {
+    let events: BTreeSet<i16> = events
+        .into_iter()
+        .chain(solved.iter().map(|key| key.event))
+        .collect();
+    let mut report = String::new();
+    for event in events {
+        let Ok(kind) = EventKind::of(event) else {
+            continue;
+        };
+        let keys = kind
+            .quests()
+            .flat_map(|quest| Part::ALL.map(|part| PuzzleKey { event, quest, part }));
+        let keys: Vec<PuzzleKey> = match selector {
+            Some(selector) => selector.expand(keys),
+            None => keys.collect(),
+        };
+        if keys.is_empty() {
+            continue;
+        }
+        writeln!(
+            report,
+            "{}: {} of {} parts have a solver, {} solved",
+            kind.name(event),
+            keys.iter().filter(|key| has_solver(key)).count(),
+            keys.len(),
+            keys.iter().filter(|key| solved.contains(key)).count(),
+        )
+        .unwrap();
+        let quests: BTreeSet<i8> = keys.iter().map(|key| key.quest).collect();
+        for quest in quests {
+            let selected = |part| keys.contains(&PuzzleKey { event, quest, part });
+            writeln!(
+                report,
+                "  quest {quest:>2}: solver {}, solved {}",
+                parts(|part| selected(part) && has_solver(&PuzzleKey { event, quest, part })),
+                parts(|part| selected(part) && solved.contains(&PuzzleKey { event, quest, part })),
+            )
+            .unwrap();
+        }
+    }
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(event: i16, quest: i8, part: Part) -> PuzzleKey {
+        PuzzleKey { event, quest, part }
+    }
+
+    #[test]
+    fn test_report() {
+        let has_solver = |key: &PuzzleKey| key.event == 1 && key.part != Part::Three;
+        let solved = HashSet::from([key(1, 2, Part::One), key(2, 3, Part::Two)]);
+        let report = report([1], None, has_solver, &solved);
+        assert_eq!(
+            vec![
+                "story 1: 6 of 9 parts have a solver, 1 solved",
+                "  quest  1: solver 1 2 -, solved - - -",
+                "  quest  2: solver 1 2 -, solved 1 - -",
+                "  quest  3: solver 1 2 -, solved - - -",
+                "story 2: 0 of 9 parts have a solver, 1 solved",
+                "  quest  1: solver - - -, solved - - -",
+                "  quest  2: solver - - -, solved - - -",
+                "  quest  3: solver - - -, solved - 2 -",
+            ],
+            report.lines().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_report_selected() {
+        let selector: Selector = "2024/19-20/2".parse().unwrap();
+        let report = report([1, 2024], Some(&selector), |_| true, &HashSet::new());
+        assert_eq!(
+            vec![
+                "event 2024: 2 of 2 parts have a solver, 0 solved",
+                "  quest 19: solver - 2 -, solved - - -",
+                "  quest 20: solver - 2 -, solved - - -",
+            ],
+            report.lines().collect::<Vec<_>>()
+        );
+    }
+}
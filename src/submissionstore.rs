@@ -0,0 +1,392 @@
+use std::collections::HashMap;
+use std::error;
+use std::ffi::OsString;
+use std::fmt;
+use std::fs;
+use std::fs::File;
+use std::fs::OpenOptions;
+use std::io;
+use std::io::Write;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::sync::MutexGuard;
+use std::time::Duration;
+use std::time::SystemTime;
+
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::ecclient::AnswerResponse;
+
+#[derive(Debug)]
+#[expect(clippy::enum_variant_names)]
+pub enum Error {
+    IoError(io::Error),
+    ParseError(PathBuf, String),
+    SerializeError(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Self::IoError(ref e) => write!(f, "failed to access the submission log: {}", e),
+            Self::ParseError(ref path, ref e) => write!(
+                f,
+                "{} exists but could not be parsed, fix or remove it: {}",
+                path.display(),
+                e
+            ),
+            Self::SerializeError(ref e) => {
+                write!(f, "failed to serialize the submission log: {}", e)
+            }
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            Self::IoError(ref e) => Some(e),
+            Self::ParseError(_, _) => None,
+            Self::SerializeError(_) => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Error {
+        Error::IoError(e)
+    }
+}
+
+/// A single answer sent to the server.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct SubmissionAttempt {
+    pub answer: String,
+    #[serde(with = "serde_millis")]
+    pub submitted_at: SystemTime,
+    pub solver_build: String,
+    #[serde(default, with = "serde_millis")]
+    pub solve_duration: Option<Duration>,
+    pub response: AnswerResponse,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct PuzzleLogEntry {
+    pub rejected_answers: Vec<String>,
+    pub accepted_answer: Option<String>,
+    pub rejected_first_characters: Vec<String>,
+    pub rejected_answer_lengths: Vec<usize>,
+    pub correct_first_character: Option<String>,
+    pub correct_answer_length: Option<usize>,
+    // Logs written before the history was kept have no attempts.
+    #[serde(default)]
+    pub attempts: Vec<SubmissionAttempt>,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct Log {
+    pub answers: HashMap<String, PuzzleLogEntry>,
+}
+
+/// Keeps other writers away from a store until dropped.
+pub enum StoreLock<'a> {
+    File { _lock_file: File },
+    Memory { _guard: MutexGuard<'a, ()> },
+}
+
+/// Where the submission log is kept.
+pub trait SubmissionStore {
+    /// Loads the log, returning None if nothing has been stored yet.
+    fn load(&self) -> Result<Option<Log>, Error>;
+
+    /// Replaces the stored log.
+    fn save(&self, log: &Log) -> Result<(), Error>;
+
+    /// Takes exclusive access to the store, so that a load-modify-save sequence does
+    /// not lose concurrent updates.
+    fn lock(&self) -> Result<StoreLock<'_>, Error>;
+}
+
+/// Appends a suffix to the file name, e.g. results.toml -> results.toml.lock.
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut path = OsString::from(path.as_os_str());
+    path.push(suffix);
+    PathBuf::from(path)
+}
+
+/// Takes an exclusive lock next to the file, so that concurrent `ec` processes cannot
+/// overwrite each other's updates.
+fn lock_file(path: &Path) -> Result<StoreLock<'static>, Error> {
+    let lock_file = OpenOptions::new()
+        .create(true)
+        .write(true)
+        .truncate(false)
+        .open(with_suffix(path, ".lock"))?;
+    lock_file.lock()?;
+    Ok(StoreLock::File {
+        _lock_file: lock_file,
+    })
+}
+
+fn read_file(path: &Path) -> Result<Option<String>, Error> {
+    match fs::read_to_string(path) {
+        Ok(contents) => Ok(Some(contents)),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Replaces the file atomically, so that a crash never leaves a truncated file behind.
+fn replace_file(path: &Path, contents: &str) -> Result<(), Error> {
+    let temp_path = with_suffix(path, ".tmp");
+    let mut file = File::create(&temp_path)?;
+    file.write_all(contents.as_bytes())?;
+    file.sync_all()?;
+    fs::rename(&temp_path, path)?;
+    Ok(())
+}
+
+/// The log as a TOML file, the original results.toml format.
+pub struct TomlStore {
+    path: PathBuf,
+}
+
+impl TomlStore {
+    pub fn new(path: &Path) -> TomlStore {
+        TomlStore {
+            path: path.to_path_buf(),
+        }
+    }
+}
+
+impl SubmissionStore for TomlStore {
+    fn load(&self) -> Result<Option<Log>, Error> {
+        let Some(contents) = read_file(&self.path)? else {
+            return Ok(None);
+        };
+        toml::from_str(contents.as_str())
+            .map(Some)
+            .map_err(|e| Error::ParseError(self.path.clone(), e.to_string()))
+    }
+
+    fn save(&self, log: &Log) -> Result<(), Error> {
+        let contents =
+            toml::to_string_pretty(log).map_err(|e| Error::SerializeError(e.to_string()))?;
+        replace_file(&self.path, contents.as_str())
+    }
+
+    fn lock(&self) -> Result<StoreLock<'_>, Error> {
+        lock_file(&self.path)
+    }
+}
+
+/// The log as a JSON file, for consumption by other tools.
+pub struct JsonStore {
+    path: PathBuf,
+}
+
+impl JsonStore {
+    pub fn new(path: &Path) -> JsonStore {
+        JsonStore {
+            path: path.to_path_buf(),
+        }
+    }
+}
+
+impl SubmissionStore for JsonStore {
+    fn load(&self) -> Result<Option<Log>, Error> {
+        let Some(contents) = read_file(&self.path)? else {
+            return Ok(None);
+        };
+        serde_json::from_str(contents.as_str())
+            .map(Some)
+            .map_err(|e| Error::ParseError(self.path.clone(), e.to_string()))
+    }
+
+    fn save(&self, log: &Log) -> Result<(), Error> {
+        let contents =
+            serde_json::to_string_pretty(log).map_err(|e| Error::SerializeError(e.to_string()))?;
+        replace_file(&self.path, contents.as_str())
+    }
+
+    fn lock(&self) -> Result<StoreLock<'_>, Error> {
+        lock_file(&self.path)
+    }
+}
+
+/// Keeps the log in memory only. Loading and saving go through TOML, so the store
+/// behaves exactly like the file it stands in for.
+#[derive(Default)]
+pub struct MemoryStore {
+    contents: Mutex<Option<String>>,
+    lock: Mutex<()>,
+}
+
+impl SubmissionStore for MemoryStore {
+    fn load(&self) -> Result<Option<Log>, Error> {
+        match self.contents.lock().unwrap().as_deref() {
+            None => Ok(None),
+            Some(contents) => toml::from_str(contents)
+                .map(Some)
+                .map_err(|e| Error::ParseError(PathBuf::from("<memory>"), e.to_string())),
+        }
+    }
+
+    fn save(&self, log: &Log) -> Result<(), Error> {
+        let contents =
+            toml::to_string_pretty(log).map_err(|e| Error::SerializeError(e.to_string()))?;
+        *self.contents.lock().unwrap() = Some(contents);
+        Ok(())
+    }
+
+    fn lock(&self) -> Result<StoreLock<'_>, Error> {
+        Ok(StoreLock::Memory {
+            _guard: self.lock.lock().unwrap(),
+        })
+    }
+}
+
+/// Opens the store for the given file, choosing the format by its extension. The
+/// special name `:memory:` keeps the log in memory, which is forgotten on exit.
+pub fn open_store(path: &Path) -> Box<dyn SubmissionStore> {
+    if path.as_os_str() == ":memory:" {
+        return Box::new(MemoryStore::default());
+    }
+    match path.extension().and_then(|e| e.to_str()) {
+        Some("json") => Box::new(JsonStore::new(path)),
+        _ => Box::new(TomlStore::new(path)),
+    }
+}
+
+/// Returns a fresh directory under the temp directory, for tests which need real files.
+/// The name must be unique among the tests.
+#[cfg(test)]
+pub fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("ec-{}-{}", std::process::id(), name));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_path(name: &str, file_name: &str) -> PathBuf {
+        temp_dir(&format!("store-{name}")).join(file_name)
+    }
+
+    fn sample_log() -> Log {
+        let mut log = Log::default();
+        log.answers.insert(
+            String::from("user1337/event2024quest6part1"),
+            PuzzleLogEntry {
+                accepted_answer: Some(String::from("forty_two")),
+                attempts: vec![SubmissionAttempt {
+                    answer: String::from("forty_two"),
+                    submitted_at: SystemTime::UNIX_EPOCH + Duration::from_millis(1755169141000),
+                    solver_build: String::from("abc1234"),
+                    solve_duration: Some(Duration::from_millis(1500)),
+                    response: AnswerResponse {
+                        correct: true,
+                        length_correct: true,
+                        first_correct: true,
+                        time: SystemTime::UNIX_EPOCH + Duration::from_millis(1755169141515),
+                        local_time: Duration::from_millis(79507010),
+                        global_time: Duration::from_millis(23803141515),
+                        global_place: 797,
+                        global_score: 0,
+                    },
+                }],
+                ..Default::default()
+            },
+        );
+        log
+    }
+
+    fn assert_round_trip(store: &dyn SubmissionStore) {
+        assert!(store.load().unwrap().is_none());
+        {
+            let _lock = store.lock().unwrap();
+            store.save(&sample_log()).unwrap();
+        }
+        let log = store.load().unwrap().unwrap();
+        let entry = &log.answers["user1337/event2024quest6part1"];
+        assert_eq!(Some(String::from("forty_two")), entry.accepted_answer);
+        let attempt = &entry.attempts[0];
+        assert_eq!("abc1234", attempt.solver_build);
+        assert_eq!(Some(Duration::from_millis(1500)), attempt.solve_duration);
+        assert_eq!(797, attempt.response.global_place);
+        assert_eq!(Duration::from_millis(79507010), attempt.response.local_time);
+    }
+
+    #[test]
+    fn test_toml_store() {
+        let path = temp_path("toml", "results.toml");
+        assert_round_trip(&TomlStore::new(&path));
+        assert!(!with_suffix(&path, ".tmp").exists());
+    }
+
+    #[test]
+    fn test_json_store() {
+        let path = temp_path("json", "results.json");
+        assert_round_trip(&JsonStore::new(&path));
+        assert!(fs::read_to_string(&path).unwrap().starts_with('{'));
+    }
+
+    #[test]
+    fn test_memory_store() {
+        assert_round_trip(&MemoryStore::default());
+    }
+
+    #[test]
+    fn test_read_toml_without_attempts() {
+        let path = temp_path("legacy", "results.toml");
+        fs::write(
+            &path,
+            r#"
+[answers.event2024quest5part2]
+rejected_answers = ["41"]
+accepted_answer = "42"
+rejected_first_characters = []
+rejected_answer_lengths = [3]
+correct_first_character = "4"
+correct_answer_length = 2
+"#,
+        )
+        .unwrap();
+        let log = TomlStore::new(&path).load().unwrap().unwrap();
+        let entry = &log.answers["event2024quest5part2"];
+        assert_eq!(Some(String::from("42")), entry.accepted_answer);
+        assert!(entry.attempts.is_empty());
+    }
+
+    #[test]
+    fn test_unparseable_file_is_an_error() {
+        let path = temp_path("unparseable", "results.toml");
+        fs::write(&path, "[answers\nthis is not toml").unwrap();
+        assert!(matches!(
+            TomlStore::new(&path).load(),
+            Err(Error::ParseError(_, _))
+        ));
+        let path = temp_path("unparseable_json", "results.json");
+        fs::write(&path, "{").unwrap();
+        assert!(matches!(
+            JsonStore::new(&path).load(),
+            Err(Error::ParseError(_, _))
+        ));
+    }
+
+    #[test]
+    fn test_open_store_by_extension() {
+        let path = temp_path("open", "results.json");
+        open_store(&path).save(&sample_log()).unwrap();
+        assert!(JsonStore::new(&path).load().unwrap().is_some());
+        let store = open_store(Path::new(":memory:"));
+        store.save(&sample_log()).unwrap();
+        assert!(store.load().unwrap().is_some());
+        assert!(!Path::new(":memory:").exists());
+    }
+}
@@ -0,0 +1,354 @@
+use std::collections::BTreeMap;
+use std::collections::btree_map::Entry;
+use std::fmt::Write;
+use std::path::PathBuf;
+use std::time::Duration;
+use std::time::SystemTime;
+
+use crate::stats;
+use crate::stats::PartStats;
+use crate::submissionstore;
+use crate::submissionstore::Error;
+
+/// How one team member fared on a puzzle part.
+#[derive(Debug, PartialEq)]
+pub struct Standing {
+    pub user_id: i64,
+    pub solved: bool,
+    /// Position among the members who solved the part, by local time. Members whose
+    /// time is unknown share the place after the timed ones.
+    pub place: Option<usize>,
+    pub solved_at: Option<SystemTime>,
+    pub local_time: Option<Duration>,
+    pub wrong_attempts: usize,
+}
+
+/// The team standings for one puzzle part, best first.
+#[derive(Debug, PartialEq)]
+pub struct Leaderboard {
+    pub event: i16,
+    pub quest: i8,
+    pub part: u8,
+    pub standings: Vec<Standing>,
+}
+
+/// Reads the submission logs of all the team members. A user appearing in several
+/// logs is only counted once per part, preferring the log where the part is solved,
+/// and then the one which knows when.
+pub fn load(paths: &[PathBuf]) -> Result<Vec<PartStats>, Error> {
+    let mut merged = BTreeMap::new();
+    for path in paths {
+        let Some(log) = submissionstore::open_store(path).load()? else {
+            log::warn!("{} does not exist, skipping it", path.display());
+            continue;
+        };
+        for part_stats in stats::collect(&log, None) {
+            let key = (
+                part_stats.event,
+                part_stats.quest,
+                part_stats.part,
+                part_stats.user_id,
+            );
+            match merged.entry(key) {
+                Entry::Vacant(e) => {
+                    e.insert(part_stats);
+                }
+                Entry::Occupied(mut e) => {
+                    if completeness(e.get()) < completeness(&part_stats) {
+                        e.insert(part_stats);
+                    }
+                }
+            }
+        }
+    }
+    Ok(merged.into_values().collect())
+}
+
+/// Whether the part is solved, and whether the time it took is known. Logs written
+/// before the attempts were recorded know the answer but not the time.
+fn completeness(s: &PartStats) -> (bool, bool) {
+    (s.accepted_answer.is_some(), s.local_time.is_some())
+}
+
+/// Groups the members' results by puzzle part. Members who solved the part are
+/// ranked by the time since the quest opened, then come those who solved it at an
+/// unknown time, and the others follow, unranked.
+pub fn leaderboards(stats: Vec<PartStats>) -> Vec<Leaderboard> {
+    let mut boards: BTreeMap<(i16, i8, u8), Vec<Standing>> = BTreeMap::new();
+    for s in stats {
+        boards
+            .entry((s.event, s.quest, s.part))
+            .or_default()
+            .push(Standing {
+                user_id: s.user_id,
+                solved: s.accepted_answer.is_some(),
+                place: None,
+                solved_at: s.solved_at,
+                local_time: s.local_time,
+                wrong_attempts: s.wrong_attempts,
+            });
+    }
+    boards
+        .into_iter()
+        .map(|((event, quest, part), mut standings)| {
+            standings.sort_by_key(|s| (!s.solved, s.local_time.is_none(), s.local_time, s.user_id));
+            let timed = standings
+                .iter()
+                .filter(|s| s.solved && s.local_time.is_some())
+                .count();
+            for (i, standing) in standings.iter_mut().enumerate() {
+                if standing.solved {
+                    standing.place = Some((i + 1).min(timed + 1));
+                }
+            }
+            Leaderboard {
+                event,
+                quest,
+                part,
+                standings,
+            }
+        })
+        .collect()
+}
+
+fn unix_millis(time: SystemTime) -> u128 {
+    time.duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis()
+}
+
+/// The time in UTC, e.g. `2025-11-03 17:42:05`.
+fn utc_timestamp(time: SystemTime) -> String {
+    let seconds = time
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    let (days, seconds) = (seconds / 86400, seconds % 86400);
+    // Converts days since the epoch to a civil date, counting in 400 year eras which
+    // start on March 1st so that the leap day comes last.
+    let days = days + 719468;
+    let (era, day_of_era) = (days / 146097, days % 146097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_from_march = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_from_march + 2) / 5 + 1;
+    let month = if month_from_march < 10 {
+        month_from_march + 3
+    } else {
+        month_from_march - 9
+    };
+    let year = era * 400 + year_of_era + u64::from(month <= 2);
+    format!(
+        "{year:04}-{month:02}-{day:02} {:02}:{:02}:{:02}",
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60
+    )
+}
+
+/// One row per member and part.
+pub fn to_csv(boards: &[Leaderboard]) -> String {
+    let mut csv =
+        String::from("event,quest,part,place,user_id,solved_at_ms,local_time_ms,wrong_attempts\n");
+    for board in boards {
+        for s in &board.standings {
+            writeln!(
+                csv,
+                "{},{},{},{},{},{},{},{}",
+                board.event,
+                board.quest,
+                board.part,
+                stats::optional(s.place),
+                s.user_id,
+                stats::optional(s.solved_at.map(unix_millis)),
+                stats::optional(s.local_time.map(|t| t.as_millis())),
+                s.wrong_attempts,
+            )
+            .unwrap();
+        }
+    }
+    csv
+}
+
+/// A table per puzzle part.
+pub fn to_markdown(boards: &[Leaderboard]) -> String {
+    let mut md = String::new();
+    for board in boards {
+        writeln!(
+            md,
+            "### Event {} quest {} part {}\n\n\
+             | place | user | solved at (UTC) | local time | wrong attempts |\n\
+             |---|---|---|---|---|",
+            board.event, board.quest, board.part
+        )
+        .unwrap();
+        for s in &board.standings {
+            writeln!(
+                md,
+                "| {} | {} | {} | {} | {} |",
+                s.place
+                    .map(|p| p.to_string())
+                    .unwrap_or_else(|| String::from("-")),
+                s.user_id,
+                match (s.solved, s.solved_at) {
+                    (_, Some(time)) => utc_timestamp(time),
+                    (true, None) => String::from("unknown"),
+                    (false, None) => String::from("-"),
+                },
+                stats::markdown_duration(s.local_time),
+                s.wrong_attempts
+            )
+            .unwrap();
+        }
+        md.push('\n');
+    }
+    md
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+    use crate::submissionstore::temp_dir;
+
+    fn part(user_id: i64, local_time_s: Option<u64>, wrong_attempts: usize) -> PartStats {
+        PartStats {
+            user_id,
+            event: 2025,
+            quest: 3,
+            part: 2,
+            accepted_answer: local_time_s.map(|_| String::from("42")),
+            wrong_attempts,
+            first_character_hints: 0,
+            length_hints: 0,
+            solved_at: local_time_s.map(|t| SystemTime::UNIX_EPOCH + Duration::from_secs(t)),
+            local_time: local_time_s.map(Duration::from_secs),
+            global_time: None,
+            rank: None,
+        }
+    }
+
+    #[test]
+    fn test_leaderboards() {
+        let boards = leaderboards(vec![
+            part(1, Some(300), 2),
+            part(2, None, 5),
+            part(3, Some(100), 0),
+        ]);
+        assert_eq!(1, boards.len());
+        let places: Vec<(i64, Option<usize>)> = boards[0]
+            .standings
+            .iter()
+            .map(|s| (s.user_id, s.place))
+            .collect();
+        assert_eq!(vec![(3, Some(1)), (1, Some(2)), (2, None)], places);
+    }
+
+    #[test]
+    fn test_legacy_entries_count_as_solved() {
+        // Solved before the attempts were recorded, so at an unknown time.
+        let legacy = |user_id| PartStats {
+            accepted_answer: Some(String::from("42")),
+            ..part(user_id, None, 1)
+        };
+        let boards = leaderboards(vec![
+            legacy(4),
+            part(2, None, 5),
+            part(1, Some(300), 2),
+            legacy(3),
+        ]);
+        let places: Vec<(i64, bool, Option<usize>)> = boards[0]
+            .standings
+            .iter()
+            .map(|s| (s.user_id, s.solved, s.place))
+            .collect();
+        assert_eq!(
+            vec![
+                (1, true, Some(1)),
+                (3, true, Some(2)),
+                (4, true, Some(2)),
+                (2, false, None)
+            ],
+            places
+        );
+        let md = to_markdown(&boards);
+        assert!(md.contains("| 2 | 3 | unknown | - | 1 |"));
+    }
+
+    #[test]
+    fn test_utc_timestamp() {
+        let time = |seconds| SystemTime::UNIX_EPOCH + Duration::from_secs(seconds);
+        assert_eq!("1970-01-01 00:00:00", utc_timestamp(time(0)));
+        assert_eq!("2000-02-29 00:00:00", utc_timestamp(time(951782400)));
+        assert_eq!("2025-08-14 10:59:01", utc_timestamp(time(1755169141)));
+    }
+
+    #[test]
+    fn test_exports() {
+        let boards = leaderboards(vec![part(1, Some(300), 2), part(2, None, 5)]);
+        let csv = to_csv(&boards);
+        assert_eq!(
+            vec![
+                "event,quest,part,place,user_id,solved_at_ms,local_time_ms,wrong_attempts",
+                "2025,3,2,1,1,300000,300000,2",
+                "2025,3,2,,2,,,5"
+            ],
+            csv.lines().collect::<Vec<_>>()
+        );
+        let md = to_markdown(&boards);
+        assert!(md.starts_with("### Event 2025 quest 3 part 2\n"));
+        assert!(md.contains("| solved at (UTC) |"));
+        assert!(md.contains("| 1 | 1 | 1970-01-01 00:05:00 |"));
+        assert!(md.contains("| - | 2 | - | - | 5 |"));
+    }
+
+    #[test]
+    fn test_load_merges_logs() {
+        let dir = temp_dir("team");
+        let alice = dir.join("alice.toml");
+        let bob = dir.join("bob.json");
+        fs::write(
+            &alice,
+            r#"
+[answers."user1/event2025quest3part2"]
+rejected_answers = ["41"]
+accepted_answer = "42"
+rejected_first_characters = []
+rejected_answer_lengths = []
+"#,
+        )
+        .unwrap();
+        fs::write(
+            &bob,
+            r#"{"answers": {
+                "user2/event2025quest3part2": {
+                    "rejected_answers": [], "accepted_answer": null,
+                    "rejected_first_characters": [], "rejected_answer_lengths": []
+                },
+                "user1/event2025quest3part2": {
+                    "rejected_answers": [], "accepted_answer": null,
+                    "rejected_first_characters": [], "rejected_answer_lengths": []
+                }
+            }}"#,
+        )
+        .unwrap();
+        // Alice's log was written before the attempts were recorded, yet it still
+        // wins over the unsolved entry that Bob's log has for her.
+        let stats = load(&[bob, alice, dir.join("missing.toml")]).unwrap();
+        assert_eq!(
+            vec![(1, 1), (2, 0)],
+            stats
+                .iter()
+                .map(|s| (s.user_id, s.wrong_attempts))
+                .collect::<Vec<_>>()
+        );
+        let places: Vec<(i64, Option<usize>)> = leaderboards(stats)[0]
+            .standings
+            .iter()
+            .map(|s| (s.user_id, s.place))
+            .collect();
+        assert_eq!(vec![(1, Some(1)), (2, None)], places);
+    }
+}
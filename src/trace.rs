@@ -0,0 +1,204 @@
+use std::fmt::Display;
+use std::fs::File;
+use std::io;
+use std::io::BufWriter;
+use std::io::Write;
+use std::path::Path;
+use std::sync::Mutex;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
+
+use serde::Serialize;
+
+/// Something a solver did, for inspecting a run step by step. `step` is the solver's
+/// own notion of time, such as a round or a search depth.
+#[derive(Debug, PartialEq, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum Event {
+    /// The state of a grid, one string per row.
+    Grid {
+        label: String,
+        step: u64,
+        rows: Vec<String>,
+    },
+    /// Something moved to, or was placed at, column `x` of row `y`.
+    Move {
+        label: String,
+        step: u64,
+        x: i64,
+        y: i64,
+    },
+    /// How many of something there are, e.g. states in a search front.
+    Count {
+        label: String,
+        step: u64,
+        count: u64,
+    },
+    /// A choice the solver made.
+    Branch {
+        label: String,
+        step: u64,
+        choice: String,
+    },
+}
+
+/// An event as recorded, along with the puzzle being solved.
+#[derive(Serialize)]
+struct Record<'a> {
+    puzzle: &'a str,
+    #[serde(flatten)]
+    event: &'a Event,
+}
+
+/// Writes events as JSON, one per line.
+struct Tracer {
+    puzzle: String,
+    out: Box<dyn Write + Send>,
+}
+
+impl Tracer {
+    fn record(&mut self, event: &Event) -> io::Result<()> {
+        let record = Record {
+            puzzle: &self.puzzle,
+            event,
+        };
+        serde_json::to_writer(&mut self.out, &record)?;
+        writeln!(self.out)
+    }
+}
+
+static TRACER: Mutex<Option<Tracer>> = Mutex::new(None);
+
+/// Whether events are recorded, so that solvers can skip building costly ones.
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// Records the events from now on in the file, replacing it.
+pub fn open(path: &Path) -> io::Result<()> {
+    let out = BufWriter::new(File::create(path)?);
+    *TRACER.lock().unwrap() = Some(Tracer {
+        puzzle: String::new(),
+        out: Box::new(out),
+    });
+    ENABLED.store(true, Ordering::Relaxed);
+    Ok(())
+}
+
+/// Tags the events from now on with the puzzle being solved.
+pub fn set_puzzle(puzzle: impl Display) {
+    if let Some(ref mut tracer) = *TRACER.lock().unwrap() {
+        tracer.puzzle = puzzle.to_string();
+    }
+}
+
+/// Writes out the buffered events.
+pub fn flush() {
+    if let Some(ref mut tracer) = *TRACER.lock().unwrap()
+        && let Err(e) = tracer.out.flush()
+    {
+        log::error!("cannot write the trace: {e}");
+    }
+}
+
+pub fn enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// Records the event, building it only if events are recorded.
+pub fn emit(event: impl FnOnce() -> Event) {
+    if !enabled() {
+        return;
+    }
+    if let Some(ref mut tracer) = *TRACER.lock().unwrap()
+        && let Err(e) = tracer.record(&event())
+    {
+        log::error!("cannot write the trace: {e}");
+    }
+}
+
+/// Records the rows of a grid. They are only rendered if events are recorded.
+pub fn grid<R: Display>(label: &str, step: u64, rows: impl IntoIterator<Item = R>) {
+    emit(|| Event::Grid {
+        label: label.to_string(),
+        step,
+        rows: rows.into_iter().map(|row| row.to_string()).collect(),
+    });
+}
+
+pub fn moved(label: &str, step: u64, (x, y): (i64, i64)) {
+    emit(|| Event::Move {
+        label: label.to_string(),
+        step,
+        x,
+        y,
+    });
+}
+
+pub fn count(label: &str, step: u64, count: usize) {
+    emit(|| Event::Count {
+        label: label.to_string(),
+        step,
+        count: count as u64,
+    });
+}
+
+pub fn branch(label: &str, step: u64, choice: impl Display) {
+    emit(|| Event::Branch {
+        label: label.to_string(),
+        step,
+        choice: choice.to_string(),
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+
+    /// A writer whose output can still be read after it was boxed.
+    #[derive(Clone, Default)]
+    struct Shared(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Shared {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_record() {
+        let out = Shared::default();
+        let mut tracer = Tracer {
+            puzzle: String::from("2024/5/1"),
+            out: Box::new(out.clone()),
+        };
+        tracer
+            .record(&Event::Move {
+                label: String::from("clapper"),
+                step: 3,
+                x: 1,
+                y: 2,
+            })
+            .unwrap();
+        tracer
+            .record(&Event::Grid {
+                label: String::from("barrels"),
+                step: 0,
+                rows: vec![String::from("#."), String::from(".#")],
+            })
+            .unwrap();
+        assert_eq!(
+            concat!(
+                r#"{"puzzle":"2024/5/1","event":"move","label":"clapper","step":3,"x":1,"y":2}"#,
+                "\n",
+                r##"{"puzzle":"2024/5/1","event":"grid","label":"barrels","step":0,"rows":["#.",".#"]}"##,
+                "\n"
+            ),
+            String::from_utf8(out.0.lock().unwrap().clone()).unwrap()
+        );
+    }
+}
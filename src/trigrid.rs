@@ -0,0 +1,232 @@
+use std::ops::Index;
+
+use crate::geometry::Point;
+use crate::grid::Grid;
+use crate::solver::SolveError;
+
+/// A triangle of a `TriGrid`, as (row, column).
+pub type Triangle = (usize, usize);
+
+/// The cell of the triangle in the text format.
+fn cell((i, j): Triangle) -> Point<usize> {
+    Point::new(j, i)
+}
+
+/// Which way a triangle points.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Orientation {
+    /// Shares its bottom edge with the row below.
+    Up,
+    /// Shares its top edge with the row above.
+    Down,
+}
+
+pub fn orientation((i, j): Triangle) -> Orientation {
+    if (i + j).is_multiple_of(2) {
+        Orientation::Down
+    } else {
+        Orientation::Up
+    }
+}
+
+/// A big triangle pointing down, tiled with small triangles which point down and up
+/// in turn. Row `i` from the top holds the triangles in columns `i` to
+/// `2 * side - i - 2`. In the text format every row is padded to `2 * side - 1`
+/// characters with `.`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TriGrid<T> {
+    cells: Grid<T>,
+}
+
+impl TriGrid<char> {
+    /// Parses a grid of characters, one row per line.
+    pub fn parse(text: &str) -> Result<TriGrid<char>, SolveError> {
+        let cells = Grid::parse(text)?;
+        if cells.columns() + 1 != 2 * cells.rows() {
+            return Err(SolveError::new(format!(
+                "a triangle with {} rows needs {} columns, found {}",
+                cells.rows(),
+                (2 * cells.rows()).saturating_sub(1),
+                cells.columns()
+            )));
+        }
+        Ok(TriGrid { cells })
+    }
+}
+
+impl<T> TriGrid<T> {
+    /// The number of rows, which is the number of triangles along each side.
+    pub fn side(&self) -> usize {
+        self.cells.rows()
+    }
+
+    pub fn contains(&self, (i, j): Triangle) -> bool {
+        i < self.side() && j >= i && j < 2 * self.side() - i - 1
+    }
+
+    pub fn triangles(&self) -> impl Iterator<Item = Triangle> + use<T> {
+        let side = self.side();
+        (0..side).flat_map(move |i| (i..2 * side - i - 1).map(move |j| (i, j)))
+    }
+
+    /// The triangles sharing an edge with the triangle.
+    pub fn neighbours(&self, (i, j): Triangle) -> impl Iterator<Item = Triangle> + use<'_, T> {
+        let vertical = match orientation((i, j)) {
+            Orientation::Down => i.wrapping_sub(1),
+            Orientation::Up => i + 1,
+        };
+        [(i, j.wrapping_sub(1)), (i, j + 1), (vertical, j)]
+            .into_iter()
+            .filter(|&triangle| self.contains(triangle))
+    }
+
+    /// The triangle whose cell ends up in `triangle` when the grid is rotated by 120
+    /// degrees clockwise.
+    pub fn rotated_from(&self, (i, j): Triangle) -> Triangle {
+        let side = self.side();
+        match orientation((i, j)) {
+            Orientation::Down => (side - (i + j) / 2 - 1, 2 * i + side - (i + j) / 2 - 1),
+            Orientation::Up => (
+                side - (i + j).div_ceil(2) - 1,
+                side - (j - i).div_ceil(2) + i,
+            ),
+        }
+    }
+
+    /// The positions of the triangles equal to the value.
+    pub fn find_all<'a>(&'a self, value: &'a T) -> impl Iterator<Item = Triangle> + 'a
+    where
+        T: PartialEq,
+    {
+        self.triangles()
+            .filter(move |&triangle| self[triangle] == *value)
+    }
+}
+
+impl<T: Clone> TriGrid<T> {
+    /// The grid rotated by 120 degrees clockwise.
+    pub fn rotate(&self) -> TriGrid<T> {
+        let mut cells = self.cells.clone();
+        for triangle in self.triangles() {
+            cells[cell(triangle)] = self[self.rotated_from(triangle)].clone();
+        }
+        TriGrid { cells }
+    }
+
+    /// The grid rotated by 0, 120 and 240 degrees clockwise.
+    pub fn rotations(&self) -> [TriGrid<T>; 3] {
+        let once = self.rotate();
+        let twice = once.rotate();
+        [self.clone(), once, twice]
+    }
+}
+
+impl<T> Index<Triangle> for TriGrid<T> {
+    type Output = T;
+
+    fn index(&self, triangle: Triangle) -> &T {
+        if !self.contains(triangle) {
+            panic!(
+                "TriGrid[] with {triangle:?} is outside the triangle of side {}",
+                self.side()
+            );
+        }
+        &self.cells[cell(triangle)]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use itertools::Itertools;
+
+    use super::*;
+
+    /// A grid of the given side with every triangle numbered, from 1 at the top left.
+    fn numbered(side: usize) -> TriGrid<usize> {
+        let mut grid = TriGrid {
+            cells: Grid::from_rows(vec![vec![0; 2 * side - 1]; side]).unwrap(),
+        };
+        for (n, triangle) in grid.triangles().enumerate() {
+            grid.cells[cell(triangle)] = n + 1;
+        }
+        grid
+    }
+
+    #[test]
+    fn test_parse() {
+        let grid = TriGrid::parse("TT#TT\n.T#T.\n..E..").unwrap();
+        assert_eq!(3, grid.side());
+        assert_eq!(9, grid.triangles().count());
+        assert_eq!(vec![(2, 2)], grid.find_all(&'E').collect::<Vec<_>>());
+        assert_eq!(6, grid.find_all(&'T').count());
+        assert_eq!(
+            "a triangle with 2 rows needs 3 columns, found 4",
+            TriGrid::parse("TTTT\n.TT.").unwrap_err().to_string()
+        );
+    }
+
+    #[test]
+    fn test_neighbours() {
+        let grid = numbered(6);
+        let neighbours = |triangle| grid.neighbours(triangle).sorted().collect::<Vec<_>>();
+        assert_eq!(vec![(0, 1)], neighbours((0, 0)));
+        assert_eq!(vec![(0, 0), (0, 2), (1, 1)], neighbours((0, 1)));
+        assert_eq!(vec![(0, 1), (0, 3)], neighbours((0, 2)));
+        assert_eq!(vec![(0, 2), (0, 4), (1, 3)], neighbours((0, 3)));
+        assert_eq!(vec![(0, 8), (0, 10), (1, 9)], neighbours((0, 9)));
+        assert_eq!(vec![(0, 9)], neighbours((0, 10)));
+        assert_eq!(vec![(0, 1), (1, 2)], neighbours((1, 1)));
+        assert_eq!(vec![(1, 1), (1, 3), (2, 2)], neighbours((1, 2)));
+        assert_eq!(vec![(1, 7), (1, 9), (2, 8)], neighbours((1, 8)));
+        assert_eq!(vec![(0, 9), (1, 8)], neighbours((1, 9)));
+    }
+
+    #[test]
+    fn test_rotate() {
+        let grid = TriGrid::parse("abcde\n.fgh.\n..i..").unwrap();
+        let rotated = grid.rotate();
+        assert_eq!("igfba\n.hdc.\n..e..", rotated.cells.to_string());
+        assert_eq!(grid.rotations()[2], rotated.rotate());
+    }
+
+    #[test]
+    fn test_neighbours_are_symmetric() {
+        for side in 1..=8 {
+            let grid = numbered(side);
+            for triangle in grid.triangles() {
+                for neighbour in grid.neighbours(triangle) {
+                    assert!(
+                        grid.neighbours(neighbour).contains(&triangle),
+                        "{neighbour:?} is a neighbour of {triangle:?} but not the other way"
+                    );
+                    assert_ne!(orientation(triangle), orientation(neighbour));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_three_rotations_are_the_identity() {
+        for side in 1..=8 {
+            let grid = numbered(side);
+            let [_, once, twice] = grid.rotations();
+            if side > 1 {
+                assert_ne!(grid, once, "side {side}");
+                assert_ne!(grid, twice, "side {side}");
+            }
+            assert_eq!(grid, twice.rotate(), "side {side}");
+            // Every triangle ends up somewhere, and neighbours stay together.
+            assert!(once.triangles().map(|t| once[t]).all_unique());
+            for triangle in grid.triangles() {
+                let from = grid.rotated_from(triangle);
+                assert!(grid.contains(from));
+                for neighbour in grid.neighbours(triangle) {
+                    assert!(
+                        grid.neighbours(from)
+                            .contains(&grid.rotated_from(neighbour))
+                    );
+                }
+            }
+        }
+    }
+}
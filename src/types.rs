@@ -1,4 +1,97 @@
-#[derive(Debug)]
+use std::error;
+use std::fmt;
+use std::ops::RangeInclusive;
+use std::str::FromStr;
+
+use serde::Deserialize;
+use serde::Serialize;
+
+/// Why a puzzle key, part or selector could not be parsed.
+#[derive(Debug, PartialEq)]
+#[expect(clippy::enum_variant_names)]
+pub enum ParseError {
+    InvalidPart(String),
+    InvalidKey(String),
+    InvalidSelector(String),
+    InvalidEvent(i16),
+    InvalidQuest(i16, i8),
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Self::InvalidPart(ref s) => write!(f, "part should be in [1,2,3], got {s:?}"),
+            Self::InvalidKey(ref s) => {
+                write!(f, "expected a puzzle like 2024/5/2 or e2024q5p2, got {s:?}")
+            }
+            Self::InvalidSelector(ref s) => write!(
+                f,
+                "expected a selector like 2024/*/*, 2025/1-10/3 or 1/*, got {s:?}"
+            ),
+            Self::InvalidEvent(event) => write!(
+                f,
+                "{event} is neither a story (1, 2, ...) nor a main event (2024, 2025, ...)"
+            ),
+            Self::InvalidQuest(event, quest) => match EventKind::of(event) {
+                Ok(kind) => write!(
+                    f,
+                    "{} has quests 1 to {}, got quest {quest}",
+                    kind.name(event),
+                    kind.quest_count()
+                ),
+                Err(e) => e.fmt(f),
+            },
+        }
+    }
+}
+
+impl error::Error for ParseError {}
+
+/// Everybody Codes runs yearly main events, numbered by year, and shorter stories,
+/// numbered from 1.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum EventKind {
+    Story,
+    Main,
+}
+
+impl EventKind {
+    pub fn of(event: i16) -> Result<EventKind, ParseError> {
+        match event {
+            1..=99 => Ok(EventKind::Story),
+            2024.. => Ok(EventKind::Main),
+            _ => Err(ParseError::InvalidEvent(event)),
+        }
+    }
+
+    pub fn quest_count(&self) -> i8 {
+        match *self {
+            Self::Story => 3,
+            Self::Main => 20,
+        }
+    }
+
+    pub fn quests(&self) -> RangeInclusive<i8> {
+        1..=self.quest_count()
+    }
+
+    /// A human readable name for the event, e.g. `story 1` or `event 2024`.
+    pub fn name(&self, event: i16) -> String {
+        match *self {
+            Self::Story => format!("story {event}"),
+            Self::Main => format!("event {event}"),
+        }
+    }
+}
+
+/// The module holding the solvers of an event, e.g. `event1` or `event2024`. Stories
+/// and main events are laid out alike.
+pub fn module_name(event: i16) -> String {
+    format!("event{event}")
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+#[serde(try_from = "u8", into = "u8")]
 pub enum Part {
     One,
     Two,
@@ -6,6 +99,8 @@ pub enum Part {
 }
 
 impl Part {
+    pub const ALL: [Part; 3] = [Part::One, Part::Two, Part::Three];
+
     pub fn as_u8(&self) -> u8 {
         match *self {
             Self::One => 1,
@@ -15,9 +110,296 @@ impl Part {
     }
 }
 
-#[derive(Debug)]
+impl TryFrom<u8> for Part {
+    type Error = ParseError;
+
+    fn try_from(part: u8) -> Result<Part, ParseError> {
+        match part {
+            1 => Ok(Part::One),
+            2 => Ok(Part::Two),
+            3 => Ok(Part::Three),
+            _ => Err(ParseError::InvalidPart(part.to_string())),
+        }
+    }
+}
+
+impl From<Part> for u8 {
+    fn from(part: Part) -> u8 {
+        part.as_u8()
+    }
+}
+
+impl FromStr for Part {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Part, ParseError> {
+        s.parse::<u8>()
+            .map_err(|_| ParseError::InvalidPart(s.to_string()))
+            .and_then(Part::try_from)
+    }
+}
+
+impl fmt::Display for Part {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.as_u8())
+    }
+}
+
+/// Identifies a puzzle. Keys order by event, then quest, then part.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
 pub struct PuzzleKey {
     pub event: i16,
     pub quest: i8,
     pub part: Part,
 }
+
+impl PuzzleKey {
+    /// Builds the key, checking that the event has such a quest.
+    pub fn new(event: i16, quest: i8, part: Part) -> Result<PuzzleKey, ParseError> {
+        if !EventKind::of(event)?.quests().contains(&quest) {
+            return Err(ParseError::InvalidQuest(event, quest));
+        }
+        Ok(PuzzleKey { event, quest, part })
+    }
+
+    /// The kind of the event. Keys built without `new` may name an unknown event.
+    pub fn event_kind(&self) -> Result<EventKind, ParseError> {
+        EventKind::of(self.event)
+    }
+
+    /// The file where the solver of the puzzle belongs.
+    pub fn source_path(&self) -> String {
+        format!("src/{}/quest{}.rs", module_name(self.event), self.quest)
+    }
+}
+
+impl FromStr for PuzzleKey {
+    type Err = ParseError;
+
+    /// Accepts `2024/5/2` as well as the compact `e2024q5p2`.
+    fn from_str(s: &str) -> Result<PuzzleKey, ParseError> {
+        let invalid = || ParseError::InvalidKey(s.to_string());
+        let (event, quest, part) = if let Some(compact) = s.strip_prefix('e') {
+            let (event, rest) = compact.split_once('q').ok_or_else(invalid)?;
+            let (quest, part) = rest.split_once('p').ok_or_else(invalid)?;
+            (event, quest, part)
+        } else {
+            let mut fields = s.split('/');
+            match (fields.next(), fields.next(), fields.next(), fields.next()) {
+                (Some(event), Some(quest), Some(part), None) => (event, quest, part),
+                _ => return Err(invalid()),
+            }
+        };
+        PuzzleKey::new(
+            event.parse().map_err(|_| invalid())?,
+            quest.parse().map_err(|_| invalid())?,
+            part.parse().map_err(|_| invalid())?,
+        )
+    }
+}
+
+impl fmt::Display for PuzzleKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}/{}/{}", self.event, self.quest, self.part)
+    }
+}
+
+/// Selects puzzles by event, quest and part, e.g. `2024/*/*`, `2025/1-10/3` or
+/// `1/*`. Each field is `*`, a number or an inclusive range, and missing trailing
+/// fields select everything.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Selector {
+    events: Option<RangeInclusive<i16>>,
+    quests: Option<RangeInclusive<i8>>,
+    parts: Option<RangeInclusive<u8>>,
+}
+
+/// Parses one selector field, with None standing for `*`.
+fn parse_selector_field<T: FromStr + PartialOrd + Copy>(
+    field: &str,
+) -> Option<Option<RangeInclusive<T>>> {
+    if field == "*" {
+        return Some(None);
+    }
+    let (start, end) = field.split_once('-').unwrap_or((field, field));
+    let start = start.parse().ok()?;
+    let end = end.parse().ok()?;
+    if start > end {
+        return None;
+    }
+    Some(Some(start..=end))
+}
+
+impl Selector {
+    pub fn matches(&self, key: &PuzzleKey) -> bool {
+        self.events.as_ref().is_none_or(|r| r.contains(&key.event))
+            && self.quests.as_ref().is_none_or(|r| r.contains(&key.quest))
+            && self
+                .parts
+                .as_ref()
+                .is_none_or(|r| r.contains(&key.part.as_u8()))
+    }
+
+    /// Returns the selected keys among the given ones, sorted.
+    pub fn expand(&self, keys: impl IntoIterator<Item = PuzzleKey>) -> Vec<PuzzleKey> {
+        let mut selected: Vec<PuzzleKey> = keys.into_iter().filter(|k| self.matches(k)).collect();
+        selected.sort();
+        selected.dedup();
+        selected
+    }
+}
+
+impl FromStr for Selector {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Selector, ParseError> {
+        let invalid = || ParseError::InvalidSelector(s.to_string());
+        let fields: Vec<&str> = s.split('/').collect();
+        if fields.len() > 3 || fields.iter().any(|f| f.is_empty()) {
+            return Err(invalid());
+        }
+        let field = |i: usize| fields.get(i).copied().unwrap_or("*");
+        let parts = parse_selector_field::<u8>(field(2)).ok_or_else(invalid)?;
+        if parts
+            .as_ref()
+            .is_some_and(|r| *r.start() < 1 || *r.end() > 3)
+        {
+            return Err(invalid());
+        }
+        Ok(Selector {
+            events: parse_selector_field(field(0)).ok_or_else(invalid)?,
+            quests: parse_selector_field(field(1)).ok_or_else(invalid)?,
+            parts,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(event: i16, quest: i8, part: Part) -> PuzzleKey {
+        PuzzleKey { event, quest, part }
+    }
+
+    #[test]
+    fn test_parse_part() {
+        assert_eq!(Ok(Part::Two), "2".parse());
+        assert_eq!(
+            Err(ParseError::InvalidPart(String::from("4"))),
+            "4".parse::<Part>()
+        );
+        assert!("two".parse::<Part>().is_err());
+        assert_eq!("3", Part::Three.to_string());
+    }
+
+    #[test]
+    fn test_parse_key() {
+        assert_eq!(Ok(key(2024, 5, Part::Two)), "2024/5/2".parse());
+        assert_eq!(Ok(key(2024, 5, Part::Two)), "e2024q5p2".parse());
+        assert_eq!(Ok(key(1, 3, Part::One)), "e1q3p1".parse());
+        for invalid in [
+            "2024/5",
+            "2024/5/2/1",
+            "2024/5/4",
+            "e2024q5",
+            "x/5/2",
+            "",
+            "1/7/1",
+            "2024/21/1",
+        ] {
+            assert!(invalid.parse::<PuzzleKey>().is_err(), "{invalid}");
+        }
+        assert_eq!("2024/5/2", key(2024, 5, Part::Two).to_string());
+    }
+
+    #[test]
+    fn test_event_kind() {
+        assert_eq!(Ok(EventKind::Story), EventKind::of(2));
+        assert_eq!(Ok(EventKind::Main), EventKind::of(2025));
+        assert_eq!(Err(ParseError::InvalidEvent(2000)), EventKind::of(2000));
+        assert_eq!(Err(ParseError::InvalidEvent(0)), EventKind::of(0));
+        assert_eq!(1..=3, EventKind::Story.quests());
+        assert_eq!(1..=20, EventKind::Main.quests());
+        assert_eq!("story 1", EventKind::Story.name(1));
+        assert_eq!("event1", module_name(1));
+        assert_eq!(Ok(EventKind::Main), key(2024, 5, Part::Two).event_kind());
+        assert_eq!(
+            Err(ParseError::InvalidEvent(1999)),
+            key(1999, 5, Part::Two).event_kind()
+        );
+        assert_eq!(
+            "src/event2024/quest5.rs",
+            key(2024, 5, Part::Two).source_path()
+        );
+    }
+
+    #[test]
+    fn test_new_key() {
+        assert_eq!(Ok(key(1, 3, Part::One)), PuzzleKey::new(1, 3, Part::One));
+        let error = PuzzleKey::new(1, 7, Part::One).unwrap_err();
+        assert_eq!(ParseError::InvalidQuest(1, 7), error);
+        assert_eq!("story 1 has quests 1 to 3, got quest 7", error.to_string());
+        assert!(PuzzleKey::new(2024, 0, Part::One).is_err());
+        assert!(PuzzleKey::new(1999, 1, Part::One).is_err());
+    }
+
+    #[test]
+    fn test_key_order() {
+        let mut keys = vec![
+            key(2025, 1, Part::One),
+            key(2024, 10, Part::One),
+            key(2024, 2, Part::Three),
+            key(2024, 2, Part::One),
+        ];
+        keys.sort();
+        assert_eq!(
+            vec![
+                key(2024, 2, Part::One),
+                key(2024, 2, Part::Three),
+                key(2024, 10, Part::One),
+                key(2025, 1, Part::One),
+            ],
+            keys
+        );
+    }
+
+    #[test]
+    fn test_key_serde() {
+        let json = serde_json::to_string(&key(2024, 5, Part::Two)).unwrap();
+        assert_eq!(r#"{"event":2024,"quest":5,"part":2}"#, json);
+        assert_eq!(
+            key(2024, 5, Part::Two),
+            serde_json::from_str::<PuzzleKey>(&json).unwrap()
+        );
+        assert!(serde_json::from_str::<PuzzleKey>(r#"{"event":1,"quest":1,"part":0}"#).is_err());
+    }
+
+    #[test]
+    fn test_selector() {
+        let all: Vec<PuzzleKey> = [1, 2024, 2025]
+            .into_iter()
+            .flat_map(|event| {
+                let quests = EventKind::of(event).unwrap().quests();
+                quests.flat_map(move |quest| Part::ALL.map(|part| key(event, quest, part)))
+            })
+            .collect();
+        let selector: Selector = "2024/*/*".parse().unwrap();
+        assert_eq!(60, selector.expand(all.clone()).len());
+        let selector: Selector = "2025/1-10/3".parse().unwrap();
+        let selected = selector.expand(all.clone());
+        assert_eq!(10, selected.len());
+        assert!(
+            selected
+                .iter()
+                .all(|k| k.event == 2025 && k.part == Part::Three)
+        );
+        let selector: Selector = "1/*".parse().unwrap();
+        assert_eq!(9, selector.expand(all.clone()).len());
+        let selector: Selector = "2024/5/2".parse().unwrap();
+        assert_eq!(vec![key(2024, 5, Part::Two)], selector.expand(all));
+        for invalid in ["", "2024//1", "2024/10-1", "2024/1/4", "2024/1/1/1", "a/*"] {
+            assert!(invalid.parse::<Selector>().is_err(), "{invalid}");
+        }
+    }
+}